@@ -9,6 +9,8 @@ pub struct Config {
 	pub bind: String,
 	pub log_level: String,
 	pub file_upload_dir: String,
+	/// memos older than this many days get purged from the trash, never when missing
+	pub trash_purge_days: Option<i64>,
 }
 
 impl Config {
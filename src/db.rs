@@ -1,10 +1,13 @@
 use crate::check_security_middleware::Security;
-use crate::routes::{GetAllMemoTitlesQuery, GetUserQuery, MemoWrite, SearchMemoQuery, LoginQuery};
+use crate::routes::{AuditQuery, GetAllMemoTitlesQuery, GetUserQuery, JumpToMemoQuery, MemoCursor, MemoSave, MemoScope, MemoSort, MemoWrite, SearchMemoQuery, LoginQuery};
 use crate::{
     errors::OrganizatorError,
-    models::{GetMemo, GetWriteMemo, MemoGroup, MemoTitle, User, Login, GetFilePermissions, ExplicitPermission},
+    models::{GetMemo, GetWriteMemo, Memo, MemoGroup, MemoJumpResult, MemoSearchResult, MemoTitle, User, Login, ExplicitPermission, MemoShare, AuditEntry,
+        MemoRevision, MemoRevisionText, SavedSearch, SearchConfig, TrashedMemo, UserGroup, UserGroupMembers,
+        Invitation, InvitationAccess, PendingInvitation, MemoGroupRow, MemoGroupTree},
 };
 use deadpool_postgres::Pool;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::SystemTime;
@@ -48,26 +51,67 @@ pub async fn get_users(pool: Arc<Pool>) -> Result<Vec<User>, OrganizatorError> {
         .collect()
 }
 
+pub async fn get_user_by_name(pool: Arc<Pool>, security: Security) -> Result<User, OrganizatorError> {
+    let sql_stmt = include_str!("sql/get_user_by_name.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql_stmt, &[Type::VARCHAR]).await?;
+    client
+        .query(&stmt, &[&security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| User::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))
+}
+
 impl GetAllMemoTitlesQuery {
-    pub fn get_statement() -> &'static str {
-        include_str!("sql/get_all_memo_titles.sql")
+    pub fn get_statement(&self) -> &'static str {
+        match self.sort {
+            MemoSort::Title => include_str!("sql/get_memo_titles_by_title.sql"),
+            MemoSort::Savetime => include_str!("sql/get_memo_titles_by_savetime.sql"),
+        }
     }
 }
+
+/// One page of the memos visible to the user, starting after the cursor
 pub async fn get_memo_titles(
-    security: Security,
     pool: Arc<Pool>,
+    query: &GetAllMemoTitlesQuery,
+    after: Option<MemoCursor>,
+    security: Security,
 ) -> Result<Vec<MemoTitle>, OrganizatorError> {
-    let sql = GetAllMemoTitlesQuery::get_statement();
+    let sql = query.get_statement();
+    let key_type = match query.sort {
+        MemoSort::Title => Type::VARCHAR,
+        MemoSort::Savetime => Type::INT8,
+    };
+    let after_id = after.as_ref().map(|cursor| cursor.id);
+    let after_title = after.as_ref().map(|cursor| cursor.key.as_str());
+    let after_savetime = match (&after, query.sort) {
+        (Some(cursor), MemoSort::Savetime) => Some(cursor.key.parse::<i64>()
+            .map_err(|_| OrganizatorError::BadRequest("Invalid cursor".to_string()))?),
+        _ => None,
+    };
+    // ask for one more to know if there is a next page
+    let limit = query.limit() + 1;
 
     let client = pool.get().await?;
     let stmt = client
-        .prepare_typed(&sql, &[Type::VARCHAR])
+        .prepare_typed(&sql, &[Type::VARCHAR, Type::VARCHAR, Type::INT4, key_type, Type::INT4, Type::INT8])
         .await
         .unwrap();
 
-    client
-        .query(&stmt, &[&security.get_user_name()])
-        .await?
+    let user_name = security.get_user_name();
+    let scope = query.scope.as_str();
+    let rows = match query.sort {
+        MemoSort::Title => client
+            .query(&stmt, &[&user_name, &scope, &query.group_id, &after_title, &after_id, &limit])
+            .await?,
+        MemoSort::Savetime => client
+            .query(&stmt, &[&user_name, &scope, &query.group_id, &after_savetime, &after_id, &limit])
+            .await?,
+    };
+    rows
         .iter()
         .map(|row| MemoTitle::from_row_ref(row).map_err(OrganizatorError::from))
         .collect()
@@ -79,27 +123,72 @@ impl SearchMemoQuery {
     }
 }
 
+/// tsquery is the search of the query already translated to the to_tsquery syntax
 pub async fn search_memo(
     pool: Arc<Pool>,
-    query: SearchMemoQuery,
+    query: &SearchMemoQuery,
+    tsquery: &str,
     security: Security,
-) -> Result<Vec<MemoTitle>, OrganizatorError> {
+) -> Result<Vec<MemoSearchResult>, OrganizatorError> {
     let sql = query.get_statement();
 
     let client = pool.get().await?;
-    let stmt = client.prepare_typed(&sql, &[Type::VARCHAR]).await.unwrap();
+    let stmt = client
+        .prepare_typed(
+            &sql,
+            &[
+                Type::VARCHAR,
+                Type::VARCHAR,
+                Type::VARCHAR,
+                Type::INT4,
+                Type::VARCHAR,
+                Type::INT8,
+                Type::INT8,
+                Type::BOOL,
+                Type::BOOL,
+            ],
+        )
+        .await
+        .unwrap();
 
     client
         .query(
             &stmt,
             &[
                 &security.get_user_name(),
-                &query.search.unwrap(),
+                &tsquery,
+                &query.scope.as_str(),
+                &query.group_id,
+                &query.owner,
+                &query.from,
+                &query.to,
+                &query.include_history,
+                &query.include_attachments,
             ],
         )
         .await?
         .iter()
-        .map(|row| MemoTitle::from_row_ref(row).map_err(OrganizatorError::from))
+        .map(|row| MemoSearchResult::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect()
+}
+
+pub async fn jump_to_memo(
+    pool: Arc<Pool>,
+    query: &JumpToMemoQuery,
+    security: Security,
+) -> Result<Vec<MemoJumpResult>, OrganizatorError> {
+    let sql = include_str!("sql/jump_to_memo.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::VARCHAR, Type::VARCHAR, Type::INT8])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&security.get_user_name(), &query.q.trim(), &query.limit()])
+        .await?
+        .iter()
+        .map(|row| MemoJumpResult::from_row_ref(row).map_err(OrganizatorError::from))
         .collect()
 }
 
@@ -131,6 +220,73 @@ pub async fn get_memo(
 
 
 
+pub async fn get_memo_history(
+    pool: Arc<Pool>,
+    id: i32,
+    security: Security,
+) -> Result<Vec<MemoRevision>, OrganizatorError> {
+    let sql = include_str!("sql/memo_history.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&id, &security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| MemoRevision::from_row(row).map_err(OrganizatorError::from))
+        .collect()
+}
+
+pub async fn get_memo_revision(
+    pool: Arc<Pool>,
+    id: i32,
+    revision_id: i32,
+    security: Security,
+) -> Result<MemoRevisionText, OrganizatorError> {
+    let sql = include_str!("sql/memo_revision.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::INT4, Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&id, &revision_id, &security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| MemoRevisionText::from_row(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap()
+}
+
+
+/// The revision saved at the given time, that is the version a client based its edit on
+pub async fn get_memo_revision_at(
+    pool: Arc<Pool>,
+    id: i32,
+    savetime: i64,
+    security: Security,
+) -> Result<Option<MemoRevisionText>, OrganizatorError> {
+    let sql = include_str!("sql/memo_revision_at.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::INT8, Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&id, &savetime, &security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| MemoRevisionText::from_row(row).map_err(OrganizatorError::from))
+        .next()
+        .transpose()
+}
+
+
 fn split_memotekst<'a>(text: &'a str) -> (Option<&'a str>, Option<&'a str>) {
     let line_end = text.find(|c| c == '\n' || c == '\r');
     match line_end {
@@ -139,6 +295,122 @@ fn split_memotekst<'a>(text: &'a str) -> (Option<&'a str>, Option<&'a str>) {
     }
 }
 
+/// Inverse of split_memotekst, memotext normally starts with the line end after the title
+pub fn join_memotekst(title: Option<&str>, memotext: Option<&str>) -> String {
+    let title = title.unwrap_or("");
+    let memotext = memotext.unwrap_or("");
+    if title.is_empty() || memotext.is_empty() || memotext.starts_with(|c| c == '\n' || c == '\r') {
+        format!("{}{}", title, memotext)
+    } else {
+        format!("{}\n{}", title, memotext)
+    }
+}
+
+/// memotext as split_memotekst stores it, starting with the line end after the title
+pub fn stored_memotext(text: String) -> String {
+    if text.is_empty() || text.starts_with(|c| c == '\n' || c == '\r') {
+        text
+    } else {
+        format!("\n{}", text)
+    }
+}
+
+impl Memo {
+    pub fn full_text(&self) -> String {
+        join_memotekst(self.title.as_deref(), self.memotext.as_deref())
+    }
+}
+
+impl MemoRevisionText {
+    pub fn full_text(&self) -> String {
+        join_memotekst(self.title.as_deref(), self.memotext.as_deref())
+    }
+}
+
+impl MemoSave {
+    pub fn full_text(&self) -> String {
+        join_memotekst(Some(&self.title), Some(&self.memotext))
+    }
+}
+
+/// The form sends the whole text, the first line is the title
+impl From<MemoWrite> for MemoSave {
+    fn from(memo: MemoWrite) -> Self {
+        let text = memo.text.unwrap_or_default();
+        let (title, memotext) = split_memotekst(&text);
+        MemoSave {
+            memo_id: memo.memo_id,
+            title: title.unwrap_or("").to_string(),
+            memotext: memotext.unwrap_or("").to_string(),
+            group_id: memo.group_id,
+            savetime: memo.savetime,
+        }
+    }
+}
+
+impl From<SavedSearch> for SearchMemoQuery {
+    fn from(saved: SavedSearch) -> Self {
+        SearchMemoQuery {
+            search: Some(saved.search),
+            group_id: saved.group_id,
+            owner: saved.owner,
+            from: saved.savetime_from,
+            to: saved.savetime_to,
+            scope: match saved.scope.as_str() {
+                "own" => MemoScope::Own,
+                "shared" => MemoScope::Shared,
+                _ => MemoScope::All,
+            },
+            include_history: saved.include_history,
+            include_attachments: saved.include_attachments,
+        }
+    }
+}
+
+/// Entries the requester may see, all of them for the admin
+pub async fn get_audit_log(
+    pool: Arc<Pool>,
+    query: &AuditQuery,
+    is_admin: bool,
+    security: Security,
+) -> Result<Vec<AuditEntry>, OrganizatorError> {
+    let sql = include_str!("sql/get_audit_log.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(
+            &sql,
+            &[
+                Type::VARCHAR,
+                Type::BOOL,
+                Type::INT4,
+                Type::INT4,
+                Type::VARCHAR,
+                Type::INT8,
+                Type::INT8,
+            ],
+        )
+        .await
+        .unwrap();
+
+    client
+        .query(
+            &stmt,
+            &[
+                &security.get_user_name(),
+                &is_admin,
+                &query.memo_group_id,
+                &query.user_group_id,
+                &query.user,
+                &query.from,
+                &query.to,
+            ],
+        )
+        .await?
+        .iter()
+        .map(|row| AuditEntry::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect()
+}
+
 #[cfg(test)]
 mod test_memotekst {
     #[test]
@@ -156,6 +428,28 @@ mod test_memotekst {
         assert_eq!(Some("ă"), split.0);
         assert_eq!(Some("\nx"), split.1);
     }
+
+    #[test]
+    fn join_split() {
+        let body = "first\nsecond";
+        let split = super::split_memotekst(&body);
+        let joined = super::join_memotekst(split.0, split.1);
+        assert_eq!(body, joined);
+    }
+
+    #[test]
+    fn stored_memotext() {
+        assert_eq!(super::stored_memotext("text".to_string()), "\ntext");
+        assert_eq!(super::stored_memotext("\ntext".to_string()), "\ntext");
+        assert_eq!(super::stored_memotext(String::new()), "");
+    }
+
+    #[test]
+    fn join_without_line_end() {
+        let joined = super::join_memotekst(Some("title"), Some("text"));
+        assert_eq!("title\ntext", joined);
+        assert_eq!("title", super::join_memotekst(Some("title"), None));
+    }
 }
 
 
@@ -168,7 +462,7 @@ fn get_millis() -> i64 {
 
 pub async fn write_memo(
     pool: Arc<Pool>,
-    memo: MemoWrite,
+    memo: &MemoSave,
     security: Security,
 ) -> Result<GetWriteMemo, OrganizatorError> {
     let client = pool.get().await?;
@@ -177,29 +471,29 @@ pub async fn write_memo(
 
     let stmt = client
         .prepare_typed(
-            "select * from memo_write($1, $2, $3, $4, $5, $6);",
+            "select * from memo_write($1, $2, $3, $4, $5, $6, $7);",
             &[
                 Type::INT4,
                 Type::VARCHAR,
                 Type::VARCHAR,
                 Type::INT8,
+                Type::INT8,
                 Type::INT4,
                 Type::VARCHAR,
             ],
         )
         .await
         .unwrap();
-    let s = memo.text.unwrap_or("".to_string());
-    let b = split_memotekst(&s);
 
     client
         .query(
             &stmt,
             &[
                 &memo.memo_id,
-                &b.0,
-                &b.1,
+                &memo.title,
+                &memo.memotext,
                 &millis,
+                &memo.savetime,
                 &memo.group_id,
                 &security.get_user_name(),
             ],
@@ -211,22 +505,58 @@ pub async fn write_memo(
         .unwrap()
 }
 
-impl MemoGroup {
-    pub fn get_all_statement() -> &'static str {
-        include_str!("sql/memo_groups_for_user.sql")
+/// Moves the memo to the trash, only the owner can do it
+pub async fn delete_memo(
+    pool: Arc<Pool>,
+    id: i32,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/delete_memo.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR, Type::INT8])
+        .await
+        .unwrap();
+
+    match client.execute(&stmt, &[&id, &security.get_user_name(), &get_millis()]).await? {
+        0 => Err(OrganizatorError::NotFound),
+        _ => Ok(()),
     }
 }
 
-pub async fn get_memo_groups(
+pub async fn restore_memo(
     pool: Arc<Pool>,
+    id: i32,
+    revision_id: i32,
     security: Security,
-) -> Result<Vec<MemoGroup>, OrganizatorError> {
-    let _stmt = MemoGroup::get_all_statement();
-    debug!("{}", _stmt);
+) -> Result<GetWriteMemo, OrganizatorError> {
+    let sql = include_str!("sql/restore_memo.sql");
+    let client = pool.get().await?;
+
+    let millis = get_millis();
+
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::INT4, Type::INT8, Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&id, &revision_id, &millis, &security.get_user_name()])
+        .await?
+        .iter_mut()
+        .map(move |row| GetWriteMemo::from_row(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap()
+}
 
+pub async fn get_trash(
+    pool: Arc<Pool>,
+    security: Security,
+) -> Result<Vec<TrashedMemo>, OrganizatorError> {
+    let sql = include_str!("sql/get_trash.sql");
     let client = pool.get().await?;
     let stmt = client
-        .prepare_typed(&_stmt, &[Type::VARCHAR])
+        .prepare_typed(&sql, &[Type::VARCHAR])
         .await
         .unwrap();
 
@@ -234,90 +564,614 @@ pub async fn get_memo_groups(
         .query(&stmt, &[&security.get_user_name()])
         .await?
         .iter()
-        .map(|row| MemoGroup::from_row_ref(row).map_err(OrganizatorError::from))
+        .map(|row| TrashedMemo::from_row_ref(row).map_err(OrganizatorError::from))
         .collect()
 }
 
-impl LoginQuery {
-    pub fn get_statement(&self) -> &'static str {
-        include_str!("sql/login.sql")
-    }
-}
-
-pub async fn get_login (
+pub async fn restore_from_trash(
     pool: &Arc<Pool>,
-    login_query: &LoginQuery,
-) -> Result<Login, OrganizatorError> {
-    let stmt = login_query.get_statement();
+    id: i32,
+    security: &Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/restore_from_trash.sql");
     let client = pool.get().await?;
-    let prepared_stmt = client
-        .prepare_typed(&stmt, &[Type::VARCHAR])
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR])
         .await
         .unwrap();
 
-    client.query(&prepared_stmt, &[&login_query.j_username])
-    .await?
-    .iter()
-    .map(|row| Login::from_row_ref(row).map_err(OrganizatorError::from))
-    .next()
-    .unwrap()
+    match client.execute(&stmt, &[&id, &security.get_user_name()]).await? {
+        0 => Err(OrganizatorError::NotFound),
+        _ => Ok(()),
+    }
 }
 
-pub async fn update_password (
-    pool: &Arc<Pool>,
-    username: &str,
-    salt: &Vec<u8>,
-    pbkdf2_hash: &Vec<u8>,
+/// Removes a memo from the trash for good, together with its history
+pub async fn purge_memo(
+    pool: Arc<Pool>,
+    id: i32,
+    security: Security,
 ) -> Result<(), OrganizatorError> {
-    let stmt = include_str!("sql/update_password.sql");
+    let sql = include_str!("sql/purge_memo.sql");
     let client = pool.get().await?;
-    let prepared_stmt = client.prepare_typed(&stmt, &[Type::BYTEA, Type::BYTEA, Type::VARCHAR])
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR])
         .await
         .unwrap();
-    client.execute(&prepared_stmt, &[&salt, &pbkdf2_hash, &username]).await?;
+
+    let purged = client.query(&stmt, &[&id, &security.get_user_name()]).await?;
+    if purged.is_empty() {
+        return Err(OrganizatorError::NotFound);
+    }
     Ok(())
 }
 
-pub async fn insert_filestore (
-    pool: &Arc<Pool>,
-    id: &Uuid,
-    username: &str,
-    filename: &str,
-    memo_group_id: &Option<i32>,
-) -> Result<(), OrganizatorError> {
-    let stmt = include_str!("sql/insert_filestore.sql");
+/// Purges the memos that have been in the trash for more than the given number of days
+pub async fn purge_trash(
+    pool: &Pool,
+    days: i64,
+) -> Result<usize, OrganizatorError> {
+    let sql = include_str!("sql/purge_trash.sql");
     let client = pool.get().await?;
-    let prepared_stmt = client.prepare_typed(&stmt, &[Type::UUID, Type::VARCHAR, Type::VARCHAR, Type::INT4, Type::INT8])
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT8])
         .await
         .unwrap();
-    let millis = get_millis();
-    client.execute(&prepared_stmt, &[&id, &username, &filename, &memo_group_id, &millis]).await?;
-    Ok(())
+
+    let older_than = get_millis() - days * 24 * 60 * 60 * 1000;
+    let purged = client.query(&stmt, &[&older_than]).await?;
+    Ok(purged.len())
 }
 
-pub async fn file_permissions (
-    pool: &Arc<Pool>,
-    id: &Uuid,
-    username: &str,
-    min_required: Option<i32>,
-) -> Result<GetFilePermissions, OrganizatorError> {
-    let stmt = include_str!("sql/get_file_security.sql");
-    let client = pool.get().await?;
-    let prepared_stmt = client
-    .prepare_typed(&stmt, &[Type::UUID, Type::VARCHAR, Type::INT4])
-    .await
-    .unwrap();
-    client.query(&prepared_stmt, &[&id, &username, &min_required]).await?
-    .iter()
-    .map(|row| GetFilePermissions::from_row(&row).map_err(OrganizatorError::from))
-    .next()
-    .unwrap()
+impl MemoGroup {
+    pub fn get_all_statement() -> &'static str {
+        include_str!("sql/memo_groups_for_user.sql")
+    }
 }
 
-pub async fn explicit_permissions(
-    pool: &Arc<Pool>,
-    username: &str,
-    id: i32
+pub async fn insert_memo_group(
+    pool: Arc<Pool>,
+    name: &str,
+    parent_id: Option<i32>,
+    security: Security,
+) -> Result<MemoGroup, OrganizatorError> {
+    let sql = include_str!("sql/insert_memo_group.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::VARCHAR, Type::VARCHAR, Type::INT4])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&security.get_user_name(), &name, &parent_id])
+        .await?
+        .iter()
+        .map(|row| MemoGroup::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))
+}
+
+pub async fn rename_memo_group(
+    pool: Arc<Pool>,
+    id: i32,
+    name: &str,
+    security: Security,
+) -> Result<MemoGroup, OrganizatorError> {
+    let sql = include_str!("sql/rename_memo_group.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR, Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&id, &security.get_user_name(), &name])
+        .await?
+        .iter()
+        .map(|row| MemoGroup::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))
+}
+
+/// None makes it a top level group
+pub async fn move_memo_group(
+    pool: Arc<Pool>,
+    id: i32,
+    parent_id: Option<i32>,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/move_memo_group.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR, Type::INT4])
+        .await
+        .unwrap();
+
+    client.execute(&stmt, &[&id, &security.get_user_name(), &parent_id]).await?;
+    Ok(())
+}
+
+/// Refused while memos or files are in the group, unless they can move to reassign_to
+pub async fn delete_memo_group(
+    pool: Arc<Pool>,
+    id: i32,
+    reassign_to: Option<i32>,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/delete_memo_group.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::VARCHAR, Type::INT4, Type::INT4])
+        .await
+        .unwrap();
+
+    client.execute(&stmt, &[&security.get_user_name(), &id, &reassign_to]).await?;
+    Ok(())
+}
+
+pub async fn get_user_groups(pool: Arc<Pool>, security: Security) -> Result<Vec<UserGroup>, OrganizatorError> {
+    let sql = include_str!("sql/user_groups_for_user.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql, &[Type::VARCHAR]).await.unwrap();
+
+    client
+        .query(&stmt, &[&security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| UserGroup::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect()
+}
+
+/// Only for the owner of the user group
+pub async fn get_user_group(
+    pool: Arc<Pool>,
+    id: i32,
+    security: Security,
+) -> Result<UserGroupMembers, OrganizatorError> {
+    let client = pool.get().await?;
+    let group_stmt = client
+        .prepare_typed(include_str!("sql/get_user_group.sql"), &[Type::INT4, Type::VARCHAR])
+        .await
+        .unwrap();
+    let usergroup = client
+        .query(&group_stmt, &[&id, &security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| UserGroup::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))?;
+
+    let members_stmt = client
+        .prepare_typed(include_str!("sql/user_group_members.sql"), &[Type::INT4])
+        .await
+        .unwrap();
+    let members = client
+        .query(&members_stmt, &[&id])
+        .await?
+        .iter()
+        .map(|row| User::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect::<Result<Vec<User>, OrganizatorError>>()?;
+
+    let invitations_stmt = client
+        .prepare_typed(include_str!("sql/user_group_invitations.sql"), &[Type::INT4])
+        .await
+        .unwrap();
+    let invitations = client
+        .query(&invitations_stmt, &[&id])
+        .await?
+        .iter()
+        .map(|row| Invitation::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect::<Result<Vec<Invitation>, OrganizatorError>>()?;
+
+    Ok(UserGroupMembers {
+        usergroup: usergroup,
+        members: members,
+        invitations: invitations,
+    })
+}
+
+pub async fn insert_user_group(
+    pool: Arc<Pool>,
+    name: &str,
+    security: Security,
+) -> Result<UserGroup, OrganizatorError> {
+    let sql = include_str!("sql/insert_user_group.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql, &[Type::VARCHAR, Type::VARCHAR]).await.unwrap();
+
+    client
+        .query(&stmt, &[&security.get_user_name(), &name])
+        .await?
+        .iter()
+        .map(|row| UserGroup::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))
+}
+
+pub async fn delete_user_group(
+    pool: Arc<Pool>,
+    id: i32,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/delete_user_group.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql, &[Type::INT4, Type::VARCHAR]).await.unwrap();
+
+    client.execute(&stmt, &[&id, &security.get_user_name()]).await?;
+    Ok(())
+}
+
+/// The user becomes a member once he accepts
+pub async fn invite_user_group_member(
+    pool: Arc<Pool>,
+    id: i32,
+    member: &str,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/invite_user_group_member.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR, Type::VARCHAR, Type::INT8])
+        .await
+        .unwrap();
+
+    client.execute(&stmt, &[&id, &security.get_user_name(), &member, &get_millis()]).await?;
+    Ok(())
+}
+
+/// Invitations of the user waiting for an answer, with the access each one gives
+pub async fn get_pending_invitations(
+    pool: Arc<Pool>,
+    security: Security,
+) -> Result<Vec<PendingInvitation>, OrganizatorError> {
+    let client = pool.get().await?;
+    let invitations_stmt = client
+        .prepare_typed(include_str!("sql/pending_invitations.sql"), &[Type::VARCHAR])
+        .await
+        .unwrap();
+    let invitations = client
+        .query(&invitations_stmt, &[&security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| Invitation::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect::<Result<Vec<Invitation>, OrganizatorError>>()?;
+
+    let access_stmt = client
+        .prepare_typed(include_str!("sql/invitation_access.sql"), &[Type::VARCHAR])
+        .await
+        .unwrap();
+    let access = client
+        .query(&access_stmt, &[&security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| InvitationAccess::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect::<Result<Vec<InvitationAccess>, OrganizatorError>>()?;
+
+    let mut pending: Vec<PendingInvitation> = invitations
+        .into_iter()
+        .map(|invitation| PendingInvitation {
+            invitation: invitation,
+            access: Vec::new(),
+        })
+        .collect();
+    for given in access {
+        if let Some(invitation) = pending.iter_mut().find(|p| p.invitation.id == given.invitation_id) {
+            invitation.access.push(given);
+        }
+    }
+    Ok(pending)
+}
+
+pub async fn answer_invitation(
+    pool: Arc<Pool>,
+    id: i32,
+    accept: bool,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/answer_invitation.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR, Type::BOOL, Type::INT8])
+        .await
+        .unwrap();
+
+    client.execute(&stmt, &[&id, &security.get_user_name(), &accept, &get_millis()]).await?;
+    Ok(())
+}
+
+pub async fn remove_user_group_member(
+    pool: Arc<Pool>,
+    id: i32,
+    member: &str,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/remove_user_group_member.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&sql, &[Type::INT4, Type::VARCHAR, Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client.execute(&stmt, &[&id, &security.get_user_name(), &member]).await?;
+    Ok(())
+}
+
+pub async fn get_saved_searches(pool: Arc<Pool>, security: Security) -> Result<Vec<SavedSearch>, OrganizatorError> {
+    let sql = include_str!("sql/get_saved_searches.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql, &[Type::VARCHAR]).await.unwrap();
+
+    client
+        .query(&stmt, &[&security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| SavedSearch::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect::<Result<Vec<SavedSearch>, OrganizatorError>>()
+}
+
+pub async fn get_saved_search(
+    pool: Arc<Pool>,
+    id: i32,
+    security: Security,
+) -> Result<SavedSearch, OrganizatorError> {
+    let sql = include_str!("sql/get_saved_search.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql, &[Type::INT4, Type::VARCHAR]).await.unwrap();
+
+    client
+        .query(&stmt, &[&id, &security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| SavedSearch::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))
+}
+
+pub async fn insert_saved_search(
+    pool: Arc<Pool>,
+    name: &str,
+    query: &SearchMemoQuery,
+    security: Security,
+) -> Result<SavedSearch, OrganizatorError> {
+    let sql = include_str!("sql/insert_saved_search.sql");
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(
+            &sql,
+            &[
+                Type::VARCHAR,
+                Type::VARCHAR,
+                Type::VARCHAR,
+                Type::INT4,
+                Type::VARCHAR,
+                Type::INT8,
+                Type::INT8,
+                Type::VARCHAR,
+                Type::BOOL,
+                Type::BOOL,
+            ],
+        )
+        .await
+        .unwrap();
+
+    client
+        .query(
+            &stmt,
+            &[
+                &security.get_user_name(),
+                &name,
+                &query.search.as_deref().unwrap_or(""),
+                &query.group_id,
+                &query.owner,
+                &query.from,
+                &query.to,
+                &query.scope.as_str(),
+                &query.include_history,
+                &query.include_attachments,
+            ],
+        )
+        .await?
+        .iter()
+        .map(|row| SavedSearch::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))
+}
+
+pub async fn delete_saved_search(
+    pool: Arc<Pool>,
+    id: i32,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let sql = include_str!("sql/delete_saved_search.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql, &[Type::INT4, Type::VARCHAR]).await.unwrap();
+
+    match client.execute(&stmt, &[&id, &security.get_user_name()]).await? {
+        0 => Err(OrganizatorError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+pub async fn get_memo_groups(
+    pool: Arc<Pool>,
+    security: Security,
+) -> Result<Vec<MemoGroupTree>, OrganizatorError> {
+    let _stmt = MemoGroup::get_all_statement();
+    debug!("{}", _stmt);
+
+    let client = pool.get().await?;
+    let stmt = client
+        .prepare_typed(&_stmt, &[Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client
+        .query(&stmt, &[&security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| MemoGroupRow::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect::<Result<Vec<MemoGroupRow>, OrganizatorError>>()
+        .map(memo_group_tree)
+}
+
+/// Nests the memo groups under their parents, a group whose parent isn't listed goes to the top
+pub fn memo_group_tree(rows: Vec<MemoGroupRow>) -> Vec<MemoGroupTree> {
+    let ids: HashSet<i32> = rows.iter().map(|row| row.id).collect();
+    let mut children: HashMap<Option<i32>, Vec<MemoGroupRow>> = HashMap::new();
+    for row in rows {
+        let parent_id = row.parent_id.filter(|id| ids.contains(id));
+        children.entry(parent_id).or_default().push(row);
+    }
+
+    fn attach(parent_id: Option<i32>, children: &mut HashMap<Option<i32>, Vec<MemoGroupRow>>) -> Vec<MemoGroupTree> {
+        children
+            .remove(&parent_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|row| MemoGroupTree {
+                id: row.id,
+                children: attach(Some(row.id), children),
+                name: row.name,
+                parent_id: row.parent_id,
+            })
+            .collect()
+    }
+    attach(None, &mut children)
+}
+
+impl LoginQuery {
+    pub fn get_statement(&self) -> &'static str {
+        include_str!("sql/login.sql")
+    }
+}
+
+pub async fn get_login (
+    pool: &Arc<Pool>,
+    login_query: &LoginQuery,
+) -> Result<Login, OrganizatorError> {
+    let stmt = login_query.get_statement();
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::VARCHAR])
+        .await
+        .unwrap();
+
+    client.query(&prepared_stmt, &[&login_query.j_username])
+    .await?
+    .iter()
+    .map(|row| Login::from_row_ref(row).map_err(OrganizatorError::from))
+    .next()
+    .unwrap()
+}
+
+/// actor is the user making the change, recorded in the audit log
+pub async fn update_password (
+    pool: &Arc<Pool>,
+    actor: &str,
+    username: &str,
+    salt: &Vec<u8>,
+    pbkdf2_hash: &Vec<u8>,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/update_password.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client.prepare_typed(&stmt, &[Type::BYTEA, Type::BYTEA, Type::VARCHAR, Type::VARCHAR])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&salt, &pbkdf2_hash, &username, &actor]).await?;
+    Ok(())
+}
+
+pub async fn get_search_config(pool: Arc<Pool>, security: Security) -> Result<SearchConfig, OrganizatorError> {
+    let sql_stmt = include_str!("sql/get_search_config.sql");
+    let client = pool.get().await?;
+    let stmt = client.prepare_typed(&sql_stmt, &[Type::VARCHAR]).await?;
+    client
+        .query(&stmt, &[&security.get_user_name()])
+        .await?
+        .iter()
+        .map(|row| SearchConfig::from_row_ref(row).map_err(OrganizatorError::from))
+        .next()
+        .unwrap_or(Err(OrganizatorError::NotFound))
+}
+
+/// Also reindexes the memos of the user
+pub async fn update_search_config(
+    pool: Arc<Pool>,
+    search_config: &Option<String>,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/update_search_config.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client.prepare_typed(&stmt, &[Type::VARCHAR, Type::TEXT])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&security.get_user_name(), &search_config]).await?;
+    Ok(())
+}
+
+pub async fn insert_filestore (
+    pool: &Arc<Pool>,
+    id: &Uuid,
+    username: &str,
+    filename: &str,
+    memo_group_id: &Option<i32>,
+    content_text: &Option<String>,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/insert_filestore.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client.prepare_typed(&stmt, &[Type::UUID, Type::VARCHAR, Type::VARCHAR, Type::INT4, Type::INT8, Type::TEXT])
+        .await
+        .unwrap();
+    let millis = get_millis();
+    let inserted = client.execute(&prepared_stmt, &[&id, &username, &filename, &memo_group_id, &millis, &content_text]).await?;
+    if inserted == 0 {
+        // rather than storing the file without a group nobody would find it in
+        return Err(OrganizatorError::Forbidden(format!(
+            "{} can not upload to memo group {:?}",
+            username, memo_group_id
+        )));
+    }
+    Ok(())
+}
+
+/// Access level as stored in memo_acl, 10 for the owner
+pub async fn memo_access(pool: Arc<Pool>, id: i32, security: Security) -> Result<i32, OrganizatorError> {
+    let stmt = include_str!("sql/memo_access.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client.prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR]).await.unwrap();
+    let row = client.query_one(&prepared_stmt, &[&id, &security.get_user_name()]).await?;
+    Ok(row.get("access"))
+}
+
+/// Same as memo_access for a memo in the trash
+pub async fn trashed_memo_access(pool: Arc<Pool>, id: i32, security: Security) -> Result<i32, OrganizatorError> {
+    let stmt = include_str!("sql/trashed_memo_access.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client.prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR]).await.unwrap();
+    let row = client.query_one(&prepared_stmt, &[&id, &security.get_user_name()]).await?;
+    Ok(row.get("access"))
+}
+
+pub async fn memo_group_access(pool: Arc<Pool>, id: i32, security: Security) -> Result<i32, OrganizatorError> {
+    let stmt = include_str!("sql/memo_group_access.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client.prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR]).await.unwrap();
+    let row = client.query_one(&prepared_stmt, &[&id, &security.get_user_name()]).await?;
+    Ok(row.get("access"))
+}
+
+pub async fn file_access(pool: Arc<Pool>, id: &Uuid, security: Security) -> Result<i32, OrganizatorError> {
+    let stmt = include_str!("sql/file_access.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client.prepare_typed(&stmt, &[Type::UUID, Type::VARCHAR]).await.unwrap();
+    let row = client.query_one(&prepared_stmt, &[&id, &security.get_user_name()]).await?;
+    Ok(row.get("access"))
+}
+
+pub async fn explicit_permissions(
+    pool: &Arc<Pool>,
+    username: &str,
+    id: i32
 ) -> Result<Vec<ExplicitPermission>, OrganizatorError> {
     let stmt = include_str!("sql/explicit_permissions.sql");
     let client = pool.get().await?;
@@ -329,4 +1183,155 @@ pub async fn explicit_permissions(
     .iter()
     .map(|row| ExplicitPermission::from_row_ref(row).map_err(OrganizatorError::from))
     .collect()
-}
\ No newline at end of file
+}
+
+pub async fn set_memo_acl(
+    pool: &Arc<Pool>,
+    username: &str,
+    id: i32,
+    user_group_id: i32,
+    access: i32,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/memo_acl_set.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR, Type::INT4, Type::INT4])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&id, &username, &user_group_id, &access]).await?;
+    Ok(())
+}
+
+pub async fn revoke_memo_acl(
+    pool: &Arc<Pool>,
+    username: &str,
+    id: i32,
+    user_group_id: i32,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/memo_acl_revoke.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR, Type::INT4])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&id, &username, &user_group_id]).await?;
+    Ok(())
+}
+
+pub async fn memo_shares(
+    pool: &Arc<Pool>,
+    username: &str,
+    id: i32,
+) -> Result<Vec<MemoShare>, OrganizatorError> {
+    let stmt = include_str!("sql/memo_shares.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR])
+        .await
+        .unwrap();
+    client.query(&prepared_stmt, &[&id, &username]).await?
+        .iter()
+        .map(|row| MemoShare::from_row_ref(row).map_err(OrganizatorError::from))
+        .collect()
+}
+
+pub async fn set_memo_share(
+    pool: &Arc<Pool>,
+    username: &str,
+    id: i32,
+    share_with: &str,
+    access: i32,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/memo_share_set.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR, Type::VARCHAR, Type::INT4])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&id, &username, &share_with, &access]).await?;
+    Ok(())
+}
+
+pub async fn revoke_memo_share(
+    pool: &Arc<Pool>,
+    username: &str,
+    id: i32,
+    share_with: &str,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/memo_share_revoke.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR, Type::VARCHAR])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&id, &username, &share_with]).await?;
+    Ok(())
+}
+
+/// The memo leaves its memo group, which stays with the previous owner
+pub async fn transfer_memo(
+    pool: Arc<Pool>,
+    id: i32,
+    new_owner: &str,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/transfer_memo.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR, Type::VARCHAR, Type::INT8])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&id, &security.get_user_name(), &new_owner, &get_millis()]).await?;
+    Ok(())
+}
+
+/// Memos and files of the memo group go along
+pub async fn transfer_memo_group(
+    pool: Arc<Pool>,
+    id: i32,
+    new_owner: &str,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let stmt = include_str!("sql/transfer_memo_group.sql");
+    let client = pool.get().await?;
+    let prepared_stmt = client
+        .prepare_typed(&stmt, &[Type::INT4, Type::VARCHAR, Type::VARCHAR, Type::INT8])
+        .await
+        .unwrap();
+    client.execute(&prepared_stmt, &[&id, &security.get_user_name(), &new_owner, &get_millis()]).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_memo_group_tree {
+    use super::memo_group_tree;
+    use crate::models::MemoGroupRow;
+
+    fn row(id: i32, name: &str, parent_id: Option<i32>) -> MemoGroupRow {
+        MemoGroupRow {
+            id,
+            name: name.to_string(),
+            parent_id,
+        }
+    }
+
+    #[test]
+    fn nests_children_in_order() {
+        let tree = memo_group_tree(vec![
+            row(3, "a", Some(2)),
+            row(1, "b", None),
+            row(4, "c", Some(2)),
+            row(2, "project", None),
+        ]);
+        assert_eq!(tree.iter().map(|g| g.id).collect::<Vec<i32>>(), vec![1, 2]);
+        assert!(tree[0].children.is_empty());
+        assert_eq!(tree[1].children.iter().map(|g| g.id).collect::<Vec<i32>>(), vec![3, 4]);
+    }
+
+    #[test]
+    fn missing_parent_goes_to_the_top() {
+        let tree = memo_group_tree(vec![row(5, "orphan", Some(99))]);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].parent_id, Some(99));
+    }
+}
@@ -0,0 +1,17 @@
+-- append only record of changes to access rights, filled by the triggers in audit_function.sql
+CREATE TABLE IF NOT EXISTS audit_log (
+  id BIGSERIAL PRIMARY KEY,
+  event varchar NOT NULL, -- acl, share, membership, ownership or password
+  changed_on int8 NOT NULL,
+  actor_id int4, -- NULL when changed outside the server
+  memo_group_id int4,
+  user_group_id int4,
+  user_id int4, -- the user the change is about
+  owner_id int4, -- owner of the memo group, memo or user group at the time of the change
+  before jsonb,
+  after jsonb
+);
+CREATE INDEX IF NOT EXISTS audit_log_changed_on_idx ON audit_log (changed_on);
+CREATE INDEX IF NOT EXISTS audit_log_memo_group_id_idx ON audit_log (memo_group_id);
+CREATE INDEX IF NOT EXISTS audit_log_user_group_id_idx ON audit_log (user_group_id);
+CREATE INDEX IF NOT EXISTS audit_log_owner_id_idx ON audit_log (owner_id);
@@ -0,0 +1,2 @@
+-- text of uploaded files, searched together with the memos
+ALTER TABLE filestore ADD COLUMN IF NOT EXISTS content_text TEXT;
@@ -0,0 +1,3 @@
+-- memo groups nest into a tree per owner, the acl of a group applies to its subgroups too
+ALTER TABLE memo_group ADD COLUMN IF NOT EXISTS parent_id int4 REFERENCES memo_group(id);
+CREATE INDEX IF NOT EXISTS memo_group_parent_id_idx ON memo_group (parent_id);
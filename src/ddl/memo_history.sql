@@ -0,0 +1,3 @@
+-- revisions are addressed by id from the history endpoints
+ALTER TABLE memo_history ADD COLUMN IF NOT EXISTS id SERIAL PRIMARY KEY;
+CREATE INDEX IF NOT EXISTS memo_history_memo_id_idx ON memo_history (memo_id);
@@ -0,0 +1,8 @@
+-- a single memo shared with a single user, besides memo_acl on the whole memo group
+CREATE TABLE IF NOT EXISTS memo_share (
+  memo_id int4 NOT NULL REFERENCES memo(id) ON DELETE CASCADE,
+  user_id int4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  access int4 NOT NULL CHECK (access IN (1, 2)), -- 1 read, 2 write as in memo_acl
+  PRIMARY KEY (memo_id, user_id)
+);
+CREATE INDEX IF NOT EXISTS memo_share_user_id_idx ON memo_share (user_id);
@@ -0,0 +1,3 @@
+-- memos deleted by their owner stay in the trash until purged
+ALTER TABLE memo ADD COLUMN IF NOT EXISTS deleted_on BIGINT;
+CREATE INDEX IF NOT EXISTS memo_deleted_on_idx ON memo (deleted_on) WHERE deleted_on IS NOT NULL;
@@ -0,0 +1,11 @@
+-- memos and memo groups given to another user, by their owner or the admin
+CREATE TABLE IF NOT EXISTS ownership_transfer (
+  id SERIAL PRIMARY KEY,
+  object_type varchar NOT NULL, -- memo or memo_group
+  object_id int4 NOT NULL,
+  from_user_id int4 NOT NULL REFERENCES users(id),
+  to_user_id int4 NOT NULL REFERENCES users(id),
+  transferred_by int4 NOT NULL REFERENCES users(id),
+  transferred_on int8 NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ownership_transfer_object_idx ON ownership_transfer (object_type, object_id);
@@ -0,0 +1,15 @@
+-- named memo searches of a user, same criteria as POST /memo/search
+CREATE TABLE IF NOT EXISTS saved_search (
+  id SERIAL PRIMARY KEY,
+  user_id int4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  name varchar NOT NULL,
+  search varchar NOT NULL,
+  group_id int4,
+  owner varchar,
+  savetime_from int8,
+  savetime_to int8,
+  scope varchar NOT NULL DEFAULT 'all',
+  include_history boolean NOT NULL DEFAULT false,
+  include_attachments boolean NOT NULL DEFAULT false,
+  UNIQUE (user_id, name)
+);
@@ -0,0 +1,7 @@
+-- text search configuration chosen by the user, the deployment default when NULL
+ALTER TABLE users ADD COLUMN IF NOT EXISTS search_config regconfig;
+-- memo text indexed with the configuration of the owner, kept up to date by memo_tsv_trigger
+ALTER TABLE memo ADD COLUMN IF NOT EXISTS search_config regconfig;
+ALTER TABLE memo ADD COLUMN IF NOT EXISTS tsv tsvector;
+-- tsv of the existing memos is filled in by server_sql/search_config_function.sql,
+-- once memo_tsv_trigger exists
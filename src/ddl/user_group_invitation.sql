@@ -0,0 +1,14 @@
+-- users join a user group only by accepting the invitation of its owner
+CREATE TABLE IF NOT EXISTS user_group_invitation (
+  id SERIAL PRIMARY KEY,
+  user_group_id int4 NOT NULL REFERENCES user_group(id) ON DELETE CASCADE,
+  user_id int4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  invited_by int4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  invited_on int8 NOT NULL,
+  status varchar NOT NULL DEFAULT 'pending', -- pending, accepted or declined
+  answered_on int8
+);
+-- one open invitation per user and group, declined ones can be repeated
+CREATE UNIQUE INDEX IF NOT EXISTS user_group_invitation_pending_idx
+  ON user_group_invitation (user_group_id, user_id) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS user_group_invitation_user_id_idx ON user_group_invitation (user_id);
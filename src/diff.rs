@@ -0,0 +1,392 @@
+use serde::{Deserialize, Serialize};
+
+/*
+ * Line or word based diff of memo texts, Myers algorithm
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Line,
+    Word,
+}
+
+impl Default for Granularity {
+    fn default() -> Self {
+        Granularity::Line
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Op {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Change {
+    pub op: Op,
+    pub text: String,
+}
+
+/// Positions are 1 based and count tokens, lines or words depending on the granularity
+#[derive(Serialize, Debug)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+    pub changes: Vec<Change>,
+}
+
+/// Splits the text in lines or words, keeping the separators so the tokens add up to the text
+pub fn tokenize(text: &str, granularity: Granularity) -> Vec<&str> {
+    match granularity {
+        Granularity::Line => text.split_inclusive('\n').collect(),
+        Granularity::Word => {
+            let mut tokens = Vec::new();
+            let mut start = 0;
+            let mut in_space = false;
+            for (i, c) in text.char_indices() {
+                if c.is_whitespace() {
+                    in_space = true;
+                } else if in_space {
+                    tokens.push(&text[start..i]);
+                    start = i;
+                    in_space = false;
+                }
+            }
+            if start < text.len() {
+                tokens.push(&text[start..]);
+            }
+            tokens
+        }
+    }
+}
+
+/// Edit script turning old into new
+pub fn diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    compare(old, new, &mut ops);
+    ops
+}
+
+/// Linear space variant of Myers: both sides are split where the forward and the backward
+/// search meet and each half is diffed on its own, so memory follows the length of the texts
+/// and not the square of the edit distance
+fn compare<T: PartialEq>(old: &[T], new: &[T], ops: &mut Vec<Op>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    ops.extend(std::iter::repeat(Op::Equal).take(prefix));
+    match middle_snake(a, b) {
+        Some((x, y)) => {
+            compare(&a[..x], &b[..y], ops);
+            compare(&a[x..], &b[y..], ops);
+        }
+        // one side is empty or nothing is shared
+        None => {
+            ops.extend(std::iter::repeat(Op::Delete).take(a.len()));
+            ops.extend(std::iter::repeat(Op::Insert).take(b.len()));
+        }
+    }
+    ops.extend(std::iter::repeat(Op::Equal).take(suffix));
+}
+
+/// Point on an optimal path where the search from the start meets the one from the end.
+/// Both sides are expected to differ at the first and at the last token.
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T]) -> Option<(usize, usize)> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    if n == 0 || m == 0 {
+        return None;
+    }
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let v_len = 2 * max_d + 2;
+    // furthest x reached on each diagonal, counted from the start for vf and from the end for vb
+    let mut vf = vec![-1isize; v_len as usize];
+    let mut vb = vec![-1isize; v_len as usize];
+    vf[(offset + 1) as usize] = 0;
+    vb[(offset + 1) as usize] = 0;
+    let delta = n - m;
+    // with an odd delta the paths meet during the forward step, otherwise during the backward one
+    let front = delta % 2 != 0;
+    // diagonals that ran off the edit graph are not searched again
+    let (mut f_start, mut f_end, mut b_start, mut b_end) = (0, 0, 0, 0);
+
+    for d in 0..max_d {
+        let mut k = -d + f_start;
+        while k <= d - f_end {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && vf[i - 1] < vf[i + 1]) {
+                vf[i + 1]
+            } else {
+                vf[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            vf[i] = x;
+            if x > n {
+                f_end += 2;
+            } else if y > m {
+                f_start += 2;
+            } else if front {
+                let j = offset + delta - k;
+                if j >= 0 && j < v_len && vb[j as usize] != -1 && x >= n - vb[j as usize] {
+                    return Some((x as usize, y as usize));
+                }
+            }
+            k += 2;
+        }
+
+        let mut k = -d + b_start;
+        while k <= d - b_end {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && vb[i - 1] < vb[i + 1]) {
+                vb[i + 1]
+            } else {
+                vb[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            vb[i] = x;
+            if x > n {
+                b_end += 2;
+            } else if y > m {
+                b_start += 2;
+            } else if !front {
+                let j = offset + delta - k;
+                if j >= 0 && j < v_len && vf[j as usize] != -1 {
+                    let fx = vf[j as usize];
+                    if fx >= n - x {
+                        return Some((fx as usize, (fx - (j - offset)) as usize));
+                    }
+                }
+            }
+            k += 2;
+        }
+    }
+    None
+}
+
+/// Groups the edit script in hunks with `context` unchanged tokens around the changes
+pub fn hunks(old: &[&str], new: &[&str], ops: &[Op], context: usize) -> Vec<Hunk> {
+    // position in old and new before applying each op
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut o, mut n) = (0, 0);
+    for op in ops {
+        positions.push((o, n));
+        match op {
+            Op::Equal => {
+                o += 1;
+                n += 1;
+            }
+            Op::Delete => o += 1,
+            Op::Insert => n += 1,
+        }
+    }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        if *op == Op::Equal {
+            continue;
+        }
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let changes: Vec<Change> = (start..end)
+                .map(|i| {
+                    let (o, n) = positions[i];
+                    let text = match ops[i] {
+                        Op::Insert => new[n],
+                        _ => old[o],
+                    };
+                    Change {
+                        op: ops[i],
+                        text: text.to_string(),
+                    }
+                })
+                .collect();
+            Hunk {
+                old_start: positions[start].0 + 1,
+                old_count: ops[start..end].iter().filter(|op| **op != Op::Insert).count(),
+                new_start: positions[start].1 + 1,
+                new_count: ops[start..end].iter().filter(|op| **op != Op::Delete).count(),
+                changes,
+            }
+        })
+        .collect()
+}
+
+/// Diff between two texts grouped in hunks
+pub fn text_diff(old: &str, new: &str, granularity: Granularity) -> Vec<Hunk> {
+    let old_tokens = tokenize(old, granularity);
+    let new_tokens = tokenize(new, granularity);
+    let ops = diff(&old_tokens, &new_tokens);
+    let context = match granularity {
+        Granularity::Line => 3,
+        Granularity::Word => 5,
+    };
+    hunks(&old_tokens, &new_tokens, &ops, context)
+}
+
+#[cfg(test)]
+mod test_diff {
+    use super::*;
+
+    fn apply<'a>(old: &[&'a str], new: &[&'a str], ops: &[Op]) -> Vec<&'a str> {
+        let (mut o, mut n) = (0, 0);
+        let mut out = Vec::new();
+        for op in ops {
+            match op {
+                Op::Equal => {
+                    assert_eq!(old[o], new[n]);
+                    out.push(old[o]);
+                    o += 1;
+                    n += 1;
+                }
+                Op::Delete => o += 1,
+                Op::Insert => {
+                    out.push(new[n]);
+                    n += 1;
+                }
+            }
+        }
+        assert_eq!(o, old.len());
+        out
+    }
+
+    #[test]
+    fn tokenize_lines() {
+        assert_eq!(tokenize("a\nb\n", Granularity::Line), vec!["a\n", "b\n"]);
+        assert_eq!(tokenize("a\nb", Granularity::Line), vec!["a\n", "b"]);
+        assert!(tokenize("", Granularity::Line).is_empty());
+    }
+
+    #[test]
+    fn tokenize_words() {
+        assert_eq!(
+            tokenize("ana  are\nmere", Granularity::Word),
+            vec!["ana  ", "are\n", "mere"]
+        );
+        assert_eq!(tokenize(" ă b", Granularity::Word), vec![" ", "ă ", "b"]);
+    }
+
+    #[test]
+    fn edit_script_rebuilds_new() {
+        let cases = [
+            ("abcabba", "cbabac"),
+            ("", "abc"),
+            ("abc", ""),
+            ("abc", "abc"),
+            ("xaby", "xcdy"),
+        ];
+        for (a, b) in cases.iter() {
+            let old: Vec<&str> = a.split("").filter(|s| !s.is_empty()).collect();
+            let new: Vec<&str> = b.split("").filter(|s| !s.is_empty()).collect();
+            let ops = diff(&old, &new);
+            assert_eq!(apply(&old, &new, &ops), new);
+        }
+    }
+
+    #[test]
+    fn minimal_edit_script() {
+        let old: Vec<char> = "abcabba".chars().collect();
+        let new: Vec<char> = "cbabac".chars().collect();
+        let edits = diff(&old, &new).into_iter().filter(|op| *op != Op::Equal).count();
+        assert_eq!(edits, 5);
+    }
+
+    #[test]
+    fn minimal_on_generated_texts() {
+        // edit count of a shortest script is len(old) + len(new) - 2 * lcs
+        fn lcs(a: &[u8], b: &[u8]) -> usize {
+            let mut row = vec![0; b.len() + 1];
+            for x in a {
+                let mut diag = 0;
+                for (j, y) in b.iter().enumerate() {
+                    let up = row[j + 1];
+                    row[j + 1] = if x == y { diag + 1 } else { up.max(row[j]) };
+                    diag = up;
+                }
+            }
+            row[b.len()]
+        }
+        let mut seed: u32 = 7;
+        let mut text = |len: usize| -> Vec<u8> {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    b"abc"[(seed >> 16) as usize % 3]
+                })
+                .collect()
+        };
+        for round in 0..300 {
+            let old = text(round % 23);
+            let new = text(round % 17);
+            let old_tokens: Vec<&str> = old.iter().map(|c| std::str::from_utf8(std::slice::from_ref(c)).unwrap()).collect();
+            let new_tokens: Vec<&str> = new.iter().map(|c| std::str::from_utf8(std::slice::from_ref(c)).unwrap()).collect();
+            let ops = diff(&old_tokens, &new_tokens);
+            assert_eq!(apply(&old_tokens, &new_tokens, &ops), new_tokens);
+            let edits = ops.iter().filter(|op| **op != Op::Equal).count();
+            assert_eq!(edits, old.len() + new.len() - 2 * lcs(&old, &new));
+        }
+    }
+
+    #[test]
+    fn long_rewrite() {
+        // completely rewritten texts used to keep a window per edit, quadratic in memory
+        let old: Vec<usize> = (0..2000).collect();
+        let new: Vec<usize> = (2000..4000).collect();
+        let ops = diff(&old, &new);
+        assert_eq!(ops.iter().filter(|op| **op == Op::Delete).count(), 2000);
+        assert_eq!(ops.iter().filter(|op| **op == Op::Insert).count(), 2000);
+    }
+
+    #[test]
+    fn line_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n";
+        let hunks = text_diff(old, new, Granularity::Line);
+        assert_eq!(hunks.len(), 1);
+        let hunk = &hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_count), (2, 7));
+        assert_eq!((hunk.new_start, hunk.new_count), (2, 7));
+        assert_eq!(hunk.changes[3].op, Op::Delete);
+        assert_eq!(hunk.changes[3].text, "5\n");
+        assert_eq!(hunk.changes[4].op, Op::Insert);
+        assert_eq!(hunk.changes[4].text, "five\n");
+    }
+
+    #[test]
+    fn distant_changes_make_separate_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "A\nb\nc\nd\ne\nf\ng\nh\ni\nJ\n";
+        assert_eq!(text_diff(old, new, Granularity::Line).len(), 2);
+        assert!(text_diff(old, old, Granularity::Line).is_empty());
+    }
+}
@@ -12,6 +12,8 @@ use actix_http::error::Error as ActixHttpError;
 use log::error;
 use actix_threadpool;
 
+use crate::models::{GetMemo, MergeConflict};
+
 #[derive(Display, From, Debug)]
 pub enum OrganizatorError {
 	NotFound,
@@ -20,27 +22,61 @@ pub enum OrganizatorError {
 	PoolError(PoolError),
 	Internal,
 	BlockingError,
+	/// invalid input, the message tells the client what to change
+	#[display(fmt = "{}", _0)]
+	#[from(ignore)]
+	BadRequest(String),
+	/// the user's access level doesn't allow the action
+	#[display(fmt = "{}", _0)]
+	#[from(ignore)]
+	Forbidden(String),
+	/// the memo was saved by somebody else meanwhile, carries the saved version; 409
+	#[display(fmt = "Conflict")]
+	#[from(ignore)]
+	Conflict(GetMemo),
+	/// concurrent edits overlap, carries the text with conflict markers; 422 so clients
+	/// can tell it apart from a plain stale write
+	#[display(fmt = "Merge conflict")]
+	#[from(ignore)]
+	MergeConflict(MergeConflict),
 }
 impl std::error::Error for OrganizatorError {}
 
+impl OrganizatorError {
+	pub fn sql_state(&self) -> Option<&str> {
+		match self {
+			OrganizatorError::PGError(err) => err.code().map(|state| state.code()),
+			_ => None,
+		}
+	}
+}
+
 impl ResponseError for OrganizatorError {
 	fn error_response(&self) -> HttpResponse {
 		match *self {
 			OrganizatorError::NotFound => HttpResponse::NotFound().finish(),
+			OrganizatorError::BadRequest(ref message) => HttpResponse::BadRequest().body(message.clone()),
+			OrganizatorError::Forbidden(ref message) => HttpResponse::Forbidden().body(message.clone()),
 			OrganizatorError::PoolError(ref err) => {
 				HttpResponse::InternalServerError().body(err.to_string())
 			}
 			OrganizatorError::PGError(ref err) => {
 				let sql_state = err.source().unwrap().downcast_ref::<DbError>().unwrap().code();
 				match sql_state.code() {
-					"2F004" => HttpResponse::Forbidden().body(err.to_string()),
+					"2F004" | "2F003" | "2F002" => HttpResponse::Forbidden().body(err.to_string()),
 					"28000" => HttpResponse::Unauthorized().body(err.to_string()),
 					"02000" => HttpResponse::NotFound().body(err.to_string()),
+					"40001" => HttpResponse::Conflict().body(err.to_string()),
+					"23505" => HttpResponse::Conflict().body(err.to_string()),
+					"23503" => HttpResponse::Conflict().body(err.to_string()),
+					"22023" => HttpResponse::BadRequest().body(err.to_string()),
 					_ => HttpResponse::InternalServerError().body(err.to_string()), 
 				}
 
 				
 			}
+			OrganizatorError::Conflict(ref memo) => HttpResponse::Conflict().json(memo),
+			OrganizatorError::MergeConflict(ref merge) => HttpResponse::UnprocessableEntity().json(merge),
 			_ => HttpResponse::InternalServerError().finish(),
 		}
 	}
@@ -0,0 +1,44 @@
+use log::warn;
+use std::panic;
+
+/// Text of an uploaded file for the search, None for formats without extractable text
+pub fn extract_text(path: &str, ext: Option<&str>) -> Option<String> {
+    let text = match ext?.to_lowercase().as_str() {
+        ".txt" | ".md" | ".markdown" => std::fs::read(path)
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+        ".pdf" => {
+            // the pdf parser panics on some malformed files
+            match panic::catch_unwind(|| pdf_extract::extract_text(path)) {
+                Ok(Ok(text)) => Some(text),
+                Ok(Err(err)) => {
+                    warn!("Could not extract the text of {}: {:?}", path, err);
+                    None
+                }
+                Err(_) => {
+                    warn!("Extracting the text of {} panicked", path);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    // postgres text can't hold NUL characters
+    text.map(|text| text.replace('\0', ""))
+}
+
+#[cfg(test)]
+mod test_file_text {
+    use super::extract_text;
+
+    #[test]
+    fn plain_text() {
+        let path = std::env::temp_dir().join("organizator_file_text_test.md");
+        std::fs::write(&path, b"# Titlu\nc\xc4\x83tre\0").unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(extract_text(path, Some(".MD")), Some("# Titlu\ncătre".to_string()));
+        assert_eq!(extract_text(path, Some(".png")), None);
+        assert_eq!(extract_text(path, None), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,13 +1,21 @@
 use actix_web::{App, HttpServer};
+use deadpool_postgres::Pool;
 use dotenv::dotenv;
+use log::{error, info};
+use std::time::Duration;
 use tokio_postgres::NoTls;
 
 mod config;
 mod db;
+mod diff;
 mod errors;
+mod file_text;
+mod merge;
 mod models;
 mod password;
+mod permissions;
 mod routes;
+mod search_query;
 
 mod check_security_middleware;
 
@@ -21,10 +29,24 @@ use env_logger::Env;
 // mod check_security_middleware;
 use check_security_middleware::CheckSecurity;
 
+/// Empties the trash of memos deleted more than `days` ago, once an hour
+async fn purge_trash(pool: Pool, days: i64) {
+    let mut interval = actix_rt::time::interval(Duration::from_secs(60 * 60));
+    loop {
+        interval.tick().await;
+        match db::purge_trash(&pool, days).await {
+            Ok(0) => {}
+            Ok(purged) => info!("Purged {} memos from the trash", purged),
+            Err(err) => error!("Could not purge the trash: {}", err),
+        }
+    }
+}
+
 /// Main test server, configurable via env variables:
 /// DB_HOST - host name of PostgreSQL DB
 /// WORKERS - number of workers (busy CPU cores)
 /// POOL_SIZE - number of DB connections per worker (busy Postgres cores)
+/// TRASH_PURGE_DAYS - days deleted memos stay in the trash, kept forever when not set
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
@@ -36,6 +58,10 @@ async fn main() -> std::io::Result<()> {
     let pool = config.pg.create_pool(NoTls).unwrap();
     let file_upload_config = FileUploadConfig {dir: config.file_upload_dir};
 
+    if let Some(days) = config.trash_purge_days {
+        actix_rt::spawn(purge_trash(pool.clone(), days));
+    }
+
     let mut key = [0; 32];
     generate_key(&mut key);
     let server = HttpServer::new(move || {
@@ -50,16 +76,56 @@ async fn main() -> std::io::Result<()> {
             .service(routes::get_users)
             .service(routes::get_memo_titles)
             .service(routes::search_memo)
+            .service(routes::get_saved_searches)
+            .service(routes::save_search)
+            .service(routes::run_saved_search)
+            .service(routes::delete_saved_search)
+            // before get_memo, jump is not a memo id
+            .service(routes::jump_to_memo)
             .service(routes::get_memo)
+            .service(routes::get_memo_permissions)
+            .service(routes::get_memo_shares)
+            .service(routes::share_memo)
+            .service(routes::unshare_memo)
+            .service(routes::transfer_memo)
+            .service(routes::get_memo_history)
+            .service(routes::get_memo_revision)
+            .service(routes::restore_memo)
+            .service(routes::memo_diff)
             .service(routes::memo_write)
+            .service(routes::memo_put)
+            .service(routes::memo_delete)
+            .service(routes::get_trash)
+            .service(routes::restore_from_trash)
+            .service(routes::purge_memo)
             .service(routes::get_memo_group)
+            .service(routes::create_memo_group)
+            .service(routes::get_memo_group_permissions)
+            .service(routes::transfer_memo_group)
+            .service(routes::rename_memo_group)
+            .service(routes::move_memo_group)
+            .service(routes::delete_memo_group)
+            .service(routes::get_user_groups)
+            .service(routes::create_user_group)
+            .service(routes::get_user_group)
+            .service(routes::delete_user_group)
+            .service(routes::invite_user_group_member)
+            .service(routes::remove_user_group_member)
+            .service(routes::get_invitations)
+            .service(routes::accept_invitation)
+            .service(routes::decline_invitation)
             .service(routes::login)
             .service(routes::logout)
             .service(routes::change_password)
+            .service(routes::get_search_config)
+            .service(routes::put_search_config)
+            .service(routes::get_audit_log)
             .service(routes::version)
             .service(routes::upload_file)
             .service(routes::file_auth)
             .service(routes::explicit_permissions)
+            .service(routes::grant_explicit_permission)
+            .service(routes::revoke_explicit_permission)
     })
     .bind(config.bind)?
     .workers(config.workers)
@@ -0,0 +1,176 @@
+use crate::diff::{diff, tokenize, Granularity, Op};
+
+/*
+ * Line based three way merge (diff3) of concurrent memo edits
+ */
+
+pub struct MergeResult {
+    pub text: String,
+    /// number of regions changed differently on both sides, marked in the text
+    pub conflicts: usize,
+}
+
+const SERVER_MARKER: &str = "<<<<<<< server\n";
+const SEPARATOR_MARKER: &str = "=======\n";
+const CLIENT_MARKER: &str = ">>>>>>> client\n";
+
+/// For every line of base the index of the same line in other, when it was kept
+fn matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matched = vec![None; base.len()];
+    let (mut o, mut n) = (0, 0);
+    for op in diff(base, other) {
+        match op {
+            Op::Equal => {
+                matched[o] = Some(n);
+                o += 1;
+                n += 1;
+            }
+            Op::Delete => o += 1,
+            Op::Insert => n += 1,
+        }
+    }
+    matched
+}
+
+fn push_lines(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        out.push_str(line);
+    }
+}
+
+/// conflict markers have to start on their own line
+fn end_line(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Merges the changes made from base to server and from base to client
+pub fn merge3(base: &str, server: &str, client: &str) -> MergeResult {
+    let base_lines = tokenize(base, Granularity::Line);
+    let server_lines = tokenize(server, Granularity::Line);
+    let client_lines = tokenize(client, Granularity::Line);
+    let in_server = matches(&base_lines, &server_lines);
+    let in_client = matches(&base_lines, &client_lines);
+
+    let mut out = String::with_capacity(server.len().max(client.len()));
+    let mut conflicts = 0;
+    let (mut o, mut s, mut c) = (0, 0, 0);
+    loop {
+        // lines unchanged on both sides
+        let mut stable = 0;
+        while o + stable < base_lines.len()
+            && in_server[o + stable] == Some(s + stable)
+            && in_client[o + stable] == Some(c + stable)
+        {
+            stable += 1;
+        }
+        if stable > 0 {
+            push_lines(&mut out, &base_lines[o..o + stable]);
+            o += stable;
+            s += stable;
+            c += stable;
+            continue;
+        }
+
+        // the changed chunk ends at the next base line kept on both sides
+        let mut next = o;
+        while next < base_lines.len() && (in_server[next].is_none() || in_client[next].is_none()) {
+            next += 1;
+        }
+        let (server_end, client_end) = if next == base_lines.len() {
+            (server_lines.len(), client_lines.len())
+        } else {
+            (in_server[next].unwrap(), in_client[next].unwrap())
+        };
+
+        let base_chunk = &base_lines[o..next];
+        let server_chunk = &server_lines[s..server_end];
+        let client_chunk = &client_lines[c..client_end];
+        if server_chunk == base_chunk {
+            push_lines(&mut out, client_chunk);
+        } else if client_chunk == base_chunk || client_chunk == server_chunk {
+            push_lines(&mut out, server_chunk);
+        } else {
+            conflicts += 1;
+            end_line(&mut out);
+            out.push_str(SERVER_MARKER);
+            push_lines(&mut out, server_chunk);
+            end_line(&mut out);
+            out.push_str(SEPARATOR_MARKER);
+            push_lines(&mut out, client_chunk);
+            end_line(&mut out);
+            out.push_str(CLIENT_MARKER);
+        }
+
+        if next == base_lines.len() {
+            break;
+        }
+        o = next;
+        s = server_end;
+        c = client_end;
+    }
+
+    MergeResult {
+        text: out,
+        conflicts,
+    }
+}
+
+#[cfg(test)]
+mod test_merge {
+    use super::merge3;
+
+    #[test]
+    fn changes_in_different_places() {
+        let base = "title\none\ntwo\nthree\nfour\n";
+        let server = "title\nONE\ntwo\nthree\nfour\n";
+        let client = "title\none\ntwo\nthree\nFOUR\n";
+        let merged = merge3(base, server, client);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "title\nONE\ntwo\nthree\nFOUR\n");
+    }
+
+    #[test]
+    fn insertions_and_deletions() {
+        let base = "a\nb\nc\nd";
+        let server = "a\nc\nd";
+        let client = "a\nb\nc\nd\ne";
+        let merged = merge3(base, server, client);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "a\nc\nd\ne");
+    }
+
+    #[test]
+    fn same_change_on_both_sides() {
+        let merged = merge3("a\nb\nc\n", "a\nB\nc\n", "a\nB\nc\n");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "a\nB\nc\n");
+    }
+
+    #[test]
+    fn conflicting_change() {
+        let merged = merge3("a\nb\nc", "a\nserver\nc", "a\nclient\nc");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "a\n<<<<<<< server\nserver\n=======\nclient\n>>>>>>> client\nc"
+        );
+    }
+
+    #[test]
+    fn conflict_on_last_line_without_line_end() {
+        let merged = merge3("a\nb", "a\nserver", "a\nclient");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.text,
+            "a\n<<<<<<< server\nserver\n=======\nclient\n>>>>>>> client\n"
+        );
+    }
+
+    #[test]
+    fn unchanged_sides() {
+        assert_eq!(merge3("a\nb", "a\nb", "a\nb").text, "a\nb");
+        assert_eq!(merge3("", "", "new").text, "new");
+    }
+}
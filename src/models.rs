@@ -2,6 +2,10 @@ use serde::Serialize;
 use tokio_pg_mapper::PostgresMapper;
 use tokio_postgres::row::Row;
 use tokio_postgres::error::Error;
+use uuid::Uuid;
+
+use crate::diff::{Granularity, Hunk};
+use crate::permissions::AccessLevel;
 
 #[derive(Serialize, PostgresMapper)]
 #[pg_mapper(table = "user")]
@@ -19,26 +23,181 @@ pub struct MemoTitle {
     pub savetime: Option<i64>,
 }
 
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "memo")]
+pub struct MemoSearchResult {
+    /// where the words were found: memo, history or attachment
+    pub source: String,
+    /// memo id, missing for attachments
+    pub id: Option<i32>,
+    /// memo_history id when found in an older revision
+    pub revision_id: Option<i32>,
+    /// filestore id when found in an uploaded file
+    pub file_id: Option<Uuid>,
+    /// file name for attachments
+    pub title: Option<String>,
+    pub user_id: i32,
+    pub savetime: Option<i64>,
+    pub rank: f32,
+    /// excerpt with the matching words highlighted
+    pub snippet: Option<String>,
+}
+
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "memo")]
+pub struct MemoJumpResult {
+    pub id: i32,
+    pub title: Option<String>,
+    pub user_id: i32,
+    pub savetime: Option<i64>,
+    /// trigram similarity with the best matching part of the title, 0 to 1
+    pub score: f32,
+}
+
 #[derive (Serialize)]
-pub struct MemoTitleList {
+pub struct MemoJumpList {
+    pub memos: Vec<MemoJumpResult>,
+}
+
+#[derive (Serialize)]
+pub struct MemoSearchList {
+    pub memos: Vec<MemoSearchResult>,
+    pub user: User,
+}
+
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "saved_search")]
+pub struct SavedSearch {
+    pub id: i32,
+    pub name: String,
+    pub search: String,
+    pub group_id: Option<i32>,
+    pub owner: Option<String>,
+    pub savetime_from: Option<i64>,
+    pub savetime_to: Option<i64>,
+    pub scope: String,
+    pub include_history: bool,
+    pub include_attachments: bool,
+}
+
+#[derive (Serialize)]
+pub struct SavedSearchList {
+    pub searches: Vec<SavedSearch>,
+}
+
+#[derive (Serialize)]
+pub struct MemoTitlePage {
     pub memos: Vec<MemoTitle>,
     pub user: User,
+    /// pass as cursor to get the following page, missing on the last page
+    pub next_cursor: Option<String>,
 }
 
 
 #[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "memo")]
+pub struct TrashedMemo {
+    pub id: i32,
+    pub title: Option<String>,
+    pub user_id: i32,
+    pub savetime: Option<i64>,
+    pub deleted_on: i64,
+}
+
+#[derive (Serialize)]
+pub struct TrashList {
+    pub memos: Vec<TrashedMemo>,
+}
+
+#[derive(Serialize, PostgresMapper, Debug)]
 #[pg_mapper(table = "memo_group")]
 pub struct MemoGroup {
     pub id: i32,
     pub name: String,
 }
 
+#[derive(Serialize, PostgresMapper, Debug)]
+#[pg_mapper(table = "memo_group")]
+pub struct MemoGroupRow {
+    pub id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+}
+
+/// Memo group with its subgroups
+#[derive (Serialize, Debug, PartialEq)]
+pub struct MemoGroupTree {
+    pub id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub children: Vec<MemoGroupTree>,
+}
+
 #[derive (Serialize)]
 pub struct MemoGroupList {
-    pub memogroups: Vec<MemoGroup>,
+    /// top level groups, the others nested in their children
+    pub memogroups: Vec<MemoGroupTree>,
+}
+
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "user_group")]
+pub struct UserGroup {
+    pub id: i32,
+    pub name: Option<String>,
+    /// owner, the only one allowed to change the members
+    pub user_id: i32,
+}
+
+#[derive (Serialize)]
+pub struct UserGroupList {
+    pub usergroups: Vec<UserGroup>,
 }
 
 #[derive (Serialize)]
+pub struct UserGroupMembers {
+    pub usergroup: UserGroup,
+    pub members: Vec<User>,
+    /// users invited who haven't answered yet
+    pub invitations: Vec<Invitation>,
+}
+
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "user_group_invitation")]
+pub struct Invitation {
+    pub id: i32,
+    pub user_group_id: i32,
+    pub user_group_name: Option<String>,
+    /// the invited user
+    pub user_id: i32,
+    pub username: Option<String>,
+    pub invited_by: Option<String>,
+    pub invited_on: i64,
+    pub status: String,
+}
+
+/// A memo group the user gets access to by accepting the invitation
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "memo_acl")]
+pub struct InvitationAccess {
+    pub invitation_id: i32,
+    pub memo_group_id: i32,
+    pub memo_group_name: String,
+    /// 1 read, 2 write
+    pub access: i32,
+}
+
+#[derive (Serialize)]
+pub struct PendingInvitation {
+    pub invitation: Invitation,
+    pub access: Vec<InvitationAccess>,
+}
+
+#[derive (Serialize)]
+pub struct PendingInvitationList {
+    pub invitations: Vec<PendingInvitation>,
+}
+
+#[derive (Serialize, Debug)]
 pub struct Memo {
     pub id:              i32,
     pub title:           Option<String>,
@@ -48,16 +207,16 @@ pub struct Memo {
     pub user:            MemoUser,
 }
 
-#[derive (Serialize)]
+#[derive (Serialize, Debug)]
 pub struct MemoUser {
     pub id: i32,
     pub name: Option<String>,
 }
 
-#[derive (Serialize)]
+#[derive (Serialize, Debug)]
 pub struct GetMemo {
-    memo: Memo,
-    user: MemoUser,
+    pub memo: Memo,
+    pub user: MemoUser,
 }
 
 impl From<&Row> for GetMemo {
@@ -97,6 +256,16 @@ impl GetMemo {
     }
 }
 
+/// Edit based on an older version that could not be merged cleanly with the saved one
+#[derive (Serialize, Debug)]
+pub struct MergeConflict {
+    pub memo:      Memo,
+    pub user:      MemoUser,
+    /// the edited text with conflict markers around the regions changed on both sides
+    pub merged:    String,
+    pub conflicts: usize,
+}
+
 #[derive (Serialize)]
 pub struct GetWriteMemo {
     memo: Option<Memo>,
@@ -148,50 +317,164 @@ impl GetWriteMemo {
     }
 }
 
-#[derive(Serialize, PostgresMapper)]
-#[pg_mapper(table = "users")]
-pub struct Login {
-    pub id: i32,
-    pub username: Option<String>,
-    pub salt: Vec<u8>,
-    pub pbkdf2: Vec<u8>,
+#[derive (Serialize)]
+pub struct MemoRevision {
+    pub id:       i32,
+    pub memo_id:  i32,
+    pub title:    Option<String>,
+    pub savetime: Option<i64>,
+    pub saveuser: MemoUser,
+}
+
+impl From<&Row> for MemoRevision {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("o_id"),
+            memo_id: row.get("o_memo_id"),
+            title: row.get("o_title"),
+            savetime: row.get("o_savetime"),
+            saveuser: MemoUser {
+                id: row.get("o_saveuser_id"),
+                name: row.get("o_saveuser_name"),
+            },
+        }
+    }
 }
 
-/*
- * File permissions is a bit overkill, most data coming back is not used
- */
+impl MemoRevision {
+    pub fn from_row(row: &Row) -> Result<Self, Error> {
+        Ok(Self::from(row))
+    }
+}
 
 #[derive (Serialize)]
-pub struct GetFilePermissions {
-    pub requester: MemoUser,
-    pub user: MemoUser,
-    pub o_memo_group_id: Option<i32>,
-    pub access: i32,
+pub struct MemoRevisionList {
+    pub revisions: Vec<MemoRevision>,
 }
 
-impl From<&Row> for GetFilePermissions {
+#[derive (Serialize)]
+pub struct MemoRevisionText {
+    pub id:        i32,
+    pub memo_id:   i32,
+    pub title:     Option<String>,
+    pub memotext:  Option<String>,
+    pub savetime:  Option<i64>,
+    pub memogroup: Option<MemoGroup>,
+    pub saveuser:  MemoUser,
+}
+
+impl From<&Row> for MemoRevisionText {
     fn from(row: &Row) -> Self {
+        let group_id: Option<i32> = row.get("o_memo_group_id");
+        let memo_group = match group_id {
+            None => None,
+            Some(id) => Some(MemoGroup {
+                id: id,
+                name: row.get("o_memo_group_name"),
+            })
+        };
+
         Self {
-            requester: MemoUser {
-                id: row.get("o_requester_id"),
-                name: row.get("io_requester_name")
-            },
-            user: MemoUser {
-                id: row.get("o_user_id"),
-                name: row.get("o_username")
+            id: row.get("o_id"),
+            memo_id: row.get("o_memo_id"),
+            title: row.get("o_title"),
+            memotext: row.get("o_memotext"),
+            savetime: row.get("o_savetime"),
+            memogroup: memo_group,
+            saveuser: MemoUser {
+                id: row.get("o_saveuser_id"),
+                name: row.get("o_saveuser_name"),
             },
-            o_memo_group_id: row.get("o_memo_group_id"),
-            access: row.get("o_access")
         }
     }
 }
 
-impl GetFilePermissions {
-    pub fn from_row(row:&Row) -> Result<Self, Error> {
+impl MemoRevisionText {
+    pub fn from_row(row: &Row) -> Result<Self, Error> {
         Ok(Self::from(row))
     }
 }
 
+#[derive (Serialize)]
+pub struct MemoDiff {
+    pub memo_id:     i32,
+    pub from:        i32,
+    /// None when comparing with the current memo
+    pub to:          Option<i32>,
+    pub granularity: Granularity,
+    pub hunks:       Vec<Hunk>,
+}
+
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "users")]
+pub struct SearchConfig {
+    /// chosen by the user, None for the deployment default
+    pub search_config: Option<String>,
+    /// the one the memos of the user are indexed with
+    pub effective: String,
+}
+
+#[derive(Serialize, PostgresMapper)]
+#[pg_mapper(table = "users")]
+pub struct Login {
+    pub id: i32,
+    pub username: Option<String>,
+    pub salt: Vec<u8>,
+    pub pbkdf2: Vec<u8>,
+}
+
+#[derive (Serialize, PostgresMapper)]
+#[pg_mapper(table = "memo_share")]
+pub struct MemoShare {
+    pub memo_id:  i32,
+    pub user_id:  i32,
+    pub username: Option<String>,
+    /// 1 read, 2 write
+    pub access:   i32,
+}
+
+#[derive (Serialize)]
+pub struct MemoShareList {
+    pub shares: Vec<MemoShare>,
+}
+
+/// What the requester can do with a memo or with the memos of a memo group
+#[derive (Serialize, Debug, PartialEq)]
+pub struct EffectivePermissions {
+    pub access:       AccessLevel,
+    pub read:         bool,
+    pub edit_text:    bool,
+    pub edit_title:   bool,
+    pub change_group: bool,
+    pub delete:       bool,
+    pub share:        bool,
+}
+
+/// Change of access rights, before and after are the changed row as json
+#[derive (Serialize, PostgresMapper)]
+#[pg_mapper(table = "audit_log")]
+pub struct AuditEntry {
+    pub id:            i64,
+    /// acl, share, membership, ownership or password
+    pub event:         String,
+    pub changed_on:    i64,
+    pub actor_id:      Option<i32>,
+    pub actor_name:    Option<String>,
+    pub memo_group_id: Option<i32>,
+    pub user_group_id: Option<i32>,
+    pub user_id:       Option<i32>,
+    pub username:      Option<String>,
+    /// owner of the changed object at the time
+    pub owner_id:      Option<i32>,
+    pub before:        Option<serde_json::Value>,
+    pub after:         Option<serde_json::Value>,
+}
+
+#[derive (Serialize)]
+pub struct AuditLog {
+    pub entries: Vec<AuditEntry>,
+}
+
 #[derive (Serialize, PostgresMapper)]
 #[pg_mapper(table = "permissions")]
 pub struct ExplicitPermission {
@@ -199,7 +482,8 @@ pub struct ExplicitPermission {
     pub memo_group_name: Option<String>,
     pub user_group_id:   i32,
     pub user_group_name: Option<String>,
-    pub user_id:         i32,
+    /// None for a user group without members
+    pub user_id:         Option<i32>,
     pub username:        Option<String>,
     pub access:          i32,
 }
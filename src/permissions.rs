@@ -0,0 +1,201 @@
+use crate::check_security_middleware::Security;
+use crate::db;
+use crate::errors::OrganizatorError;
+use crate::models::EffectivePermissions;
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/*
+ * Who can do what with memos, memo groups and uploaded files.
+ * The access level comes from the database (access_level_function.sql), the rules
+ * mirror the ones memo_write enforces: the owner can do anything, write access
+ * (memo_acl level 2) allows editing the text, read access (level 1) only reading.
+ */
+
+/// The admin can change the password of other users and give away their memos
+pub const ADMIN_USER_ID: i32 = 1;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLevel {
+    None,
+    Read,
+    Write,
+    Owner,
+}
+
+impl AccessLevel {
+    /// memo_acl stores 1 for read and 2 for write, the access functions answer 10 for the owner
+    pub fn from_db(access: i32) -> Self {
+        match access {
+            a if a >= 10 => AccessLevel::Owner,
+            a if a >= 2 => AccessLevel::Write,
+            1 => AccessLevel::Read,
+            _ => AccessLevel::None,
+        }
+    }
+
+    pub fn allows(self, action: Action) -> bool {
+        self >= action.required()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Read,
+    EditText,
+    EditTitle,
+    ChangeGroup,
+    Delete,
+    /// change who else has access
+    Share,
+    /// add a file to a memo group
+    Upload,
+    /// give it to another user
+    Transfer,
+}
+
+impl Action {
+    pub fn required(self) -> AccessLevel {
+        match self {
+            Action::Read => AccessLevel::Read,
+            Action::EditText | Action::Upload => AccessLevel::Write,
+            Action::EditTitle | Action::ChangeGroup | Action::Delete | Action::Share | Action::Transfer => {
+                AccessLevel::Owner
+            }
+        }
+    }
+}
+
+impl From<AccessLevel> for EffectivePermissions {
+    fn from(level: AccessLevel) -> Self {
+        EffectivePermissions {
+            access: level,
+            read: level.allows(Action::Read),
+            edit_text: level.allows(Action::EditText),
+            edit_title: level.allows(Action::EditTitle),
+            change_group: level.allows(Action::ChangeGroup),
+            delete: level.allows(Action::Delete),
+            share: level.allows(Action::Share),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Resource {
+    Memo(i32),
+    /// a memo in the trash, only reachable for restoring or purging it
+    TrashedMemo(i32),
+    MemoGroup(i32),
+    File(Uuid),
+}
+
+pub async fn access_level(
+    pool: Arc<Pool>,
+    resource: &Resource,
+    security: Security,
+) -> Result<AccessLevel, OrganizatorError> {
+    let access = match resource {
+        Resource::Memo(id) => db::memo_access(pool, *id, security).await?,
+        Resource::TrashedMemo(id) => db::trashed_memo_access(pool, *id, security).await?,
+        Resource::MemoGroup(id) => db::memo_group_access(pool, *id, security).await?,
+        Resource::File(id) => db::file_access(pool, id, security).await?,
+    };
+    Ok(AccessLevel::from_db(access))
+}
+
+/// Forbidden unless the user can do the action, NotFound when the resource doesn't exist
+pub async fn check(
+    pool: Arc<Pool>,
+    resource: Resource,
+    action: Action,
+    security: Security,
+) -> Result<AccessLevel, OrganizatorError> {
+    let level = access_level(pool, &resource, security).await?;
+    require(level, &resource, action)?;
+    Ok(level)
+}
+
+/// Same as check with a level already fetched, for further actions on the same resource
+pub fn require(level: AccessLevel, resource: &Resource, action: Action) -> Result<(), OrganizatorError> {
+    if level.allows(action) {
+        Ok(())
+    } else {
+        Err(OrganizatorError::Forbidden(format!(
+            "{:?} on {:?} needs {:?} access, not {:?}",
+            action,
+            resource,
+            action.required(),
+            level
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test_permissions {
+    use super::{AccessLevel, Action};
+    use crate::models::EffectivePermissions;
+
+    #[test]
+    fn from_db() {
+        assert_eq!(AccessLevel::from_db(0), AccessLevel::None);
+        assert_eq!(AccessLevel::from_db(1), AccessLevel::Read);
+        assert_eq!(AccessLevel::from_db(2), AccessLevel::Write);
+        assert_eq!(AccessLevel::from_db(10), AccessLevel::Owner);
+        assert_eq!(AccessLevel::from_db(-1), AccessLevel::None);
+    }
+
+    #[test]
+    fn read_access() {
+        assert!(AccessLevel::Read.allows(Action::Read));
+        assert!(!AccessLevel::Read.allows(Action::EditText));
+        assert!(!AccessLevel::Read.allows(Action::Upload));
+        assert!(!AccessLevel::None.allows(Action::Read));
+    }
+
+    #[test]
+    fn write_access_edits_only_the_text() {
+        assert!(AccessLevel::Write.allows(Action::Read));
+        assert!(AccessLevel::Write.allows(Action::EditText));
+        assert!(AccessLevel::Write.allows(Action::Upload));
+        assert!(!AccessLevel::Write.allows(Action::EditTitle));
+        assert!(!AccessLevel::Write.allows(Action::ChangeGroup));
+        assert!(!AccessLevel::Write.allows(Action::Delete));
+        assert!(!AccessLevel::Write.allows(Action::Share));
+    }
+
+    #[test]
+    fn upload_into_shared_group() {
+        // insert_filestore.sql accepts any group with memo_group_access >= 2
+        assert!(AccessLevel::from_db(2).allows(Action::Upload));
+        assert!(!AccessLevel::from_db(1).allows(Action::Upload));
+        assert_eq!(Action::Upload.required(), AccessLevel::Write);
+    }
+
+    #[test]
+    fn owner_can_do_anything() {
+        for action in &[
+            Action::Read,
+            Action::EditText,
+            Action::EditTitle,
+            Action::ChangeGroup,
+            Action::Delete,
+            Action::Share,
+            Action::Upload,
+            Action::Transfer,
+        ] {
+            assert!(AccessLevel::Owner.allows(*action));
+        }
+    }
+
+    #[test]
+    fn effective_permissions() {
+        let write = EffectivePermissions::from(AccessLevel::Write);
+        assert!(write.read && write.edit_text);
+        assert!(!write.edit_title && !write.change_group && !write.delete && !write.share);
+        let none = EffectivePermissions::from(AccessLevel::None);
+        assert!(!none.read && !none.edit_text);
+    }
+}
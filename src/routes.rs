@@ -1,11 +1,16 @@
 use crate::{
     db,
+    diff::{self, Granularity},
     errors::OrganizatorError,
+    file_text,
+    merge,
     password::{compute_new_password, verify_password, CREDENTIAL_LEN},
+    permissions::{self, Action, Resource},
+    search_query,
 };
 use actix_web::{
-    get, post, put, web,
-    web::{Data, Form, Query},
+    delete, get, post, put, web,
+    web::{Data, Form, Json, Query},
     HttpRequest, HttpResponse,
 };
 use deadpool_postgres::Pool;
@@ -13,13 +18,15 @@ use log::{ debug, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::check_security_middleware::Security;
-use crate::models::{MemoGroupList, MemoTitleList, User};
+use crate::models::{AuditLog, EffectivePermissions, GetWriteMemo, MemoDiff, MemoGroupList, MemoJumpList, MemoRevisionList, MemoSearchList, MemoShareList, MemoTitle, MemoTitlePage, MergeConflict, PendingInvitationList, SavedSearchList, TrashList, UserGroupList};
 use actix_multipart::Multipart;
 use actix_session::Session;
 use futures::{StreamExt, TryStreamExt};
 
 use std::io::Write;
+use std::sync::Arc;
 
+use data_encoding::BASE64URL_NOPAD;
 use uuid::Uuid;
 use std::str::FromStr;
 use crate::config::{FileUploadConfig };
@@ -57,34 +64,182 @@ pub async fn get_users(
 }
 
 
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoSort {
+    Title,
+    /// newest first
+    Savetime,
+}
+
+impl Default for MemoSort {
+    fn default() -> Self {
+        MemoSort::Title
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoScope {
+    All,
+    Own,
+    Shared,
+}
+
+impl Default for MemoScope {
+    fn default() -> Self {
+        MemoScope::All
+    }
+}
+
+impl MemoScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemoScope::All => "all",
+            MemoScope::Own => "own",
+            MemoScope::Shared => "shared",
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct GetAllMemoTitlesQuery {
-    pub username: Option<String>,
+    /// next_cursor of the previous page
+    pub cursor: Option<String>,
+    /// page size, 100 when missing and at most 500
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub sort: MemoSort,
+    #[serde(default)]
+    pub scope: MemoScope,
+    pub group_id: Option<i32>,
+}
+
+impl GetAllMemoTitlesQuery {
+    const DEFAULT_LIMIT: i64 = 100;
+    const MAX_LIMIT: i64 = 500;
+
+    /// non-positive limits are rejected by the route
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).min(Self::MAX_LIMIT)
+    }
+}
+
+/// Position of the last memo of a page, id and sort key
+#[derive(Debug, PartialEq)]
+pub struct MemoCursor {
+    pub id: i32,
+    pub key: String,
+}
+
+impl MemoCursor {
+    fn new(memo: &MemoTitle, sort: MemoSort) -> Self {
+        let key = match sort {
+            MemoSort::Title => memo.title.clone().unwrap_or_default(),
+            MemoSort::Savetime => memo.savetime.unwrap_or(0).to_string(),
+        };
+        MemoCursor { id: memo.id, key: key }
+    }
+
+    fn encode(&self) -> String {
+        BASE64URL_NOPAD.encode(format!("{}:{}", self.id, self.key).as_bytes())
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let decoded = String::from_utf8(BASE64URL_NOPAD.decode(cursor.as_bytes()).ok()?).ok()?;
+        let colon = decoded.find(':')?;
+        Some(MemoCursor {
+            id: decoded[..colon].parse().ok()?,
+            key: decoded[colon + 1..].to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_memo_cursor {
+    use super::{MemoCursor, MemoSort};
+    use crate::models::MemoTitle;
+
+    #[test]
+    fn round_trip() {
+        let memo = MemoTitle {
+            id: 12,
+            title: Some("ăla: bala".to_string()),
+            user_id: 1,
+            savetime: Some(1600000000000),
+        };
+        let cursor = MemoCursor::new(&memo, MemoSort::Title);
+        assert_eq!(MemoCursor::decode(&cursor.encode()), Some(cursor));
+        let cursor = MemoCursor::new(&memo, MemoSort::Savetime);
+        assert_eq!(cursor.key, "1600000000000");
+        assert_eq!(MemoCursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn invalid_cursor() {
+        assert_eq!(MemoCursor::decode("not base64!"), None);
+        assert_eq!(MemoCursor::decode("bm9jb2xvbg"), None); // "nocolon"
+    }
 }
 
+/// Titles of the memos one page at a time, follow next_cursor for the rest.
+/// Without a limit only the first 100 come back, clients that expected the whole list
+/// in one response have to page through it.
 #[get("/memo/")]
 pub async fn get_memo_titles(
+    qry: Query<GetAllMemoTitlesQuery>,
     security: Security,
     db_pool: Data<Pool>,
 ) -> Result<HttpResponse, OrganizatorError> {
     debug!("Memos for user name {:#?}", security.user_name);
+    let query = qry.into_inner();
+    let db_pool = db_pool.into_inner();
+
+    if query.limit.map_or(false, |limit| limit < 1) {
+        return Err(OrganizatorError::BadRequest("limit has to be positive".to_string()));
+    }
+    let after = match &query.cursor {
+        Some(cursor) => Some(MemoCursor::decode(cursor)
+            .ok_or_else(|| OrganizatorError::BadRequest("Invalid cursor".to_string()))?),
+        None => None,
+    };
 
-    let mut titles = db::get_memo_titles(security, db_pool.into_inner()).await?;
-    let owner_entry = titles.pop().unwrap();
-    let owner = User {
-        id: owner_entry.user_id,
-        username: owner_entry.title,
+    let mut titles = db::get_memo_titles(db_pool.clone(), &query, after, security.clone()).await?;
+    // one more memo than asked for tells there is another page
+    let limit = query.limit();
+    let next_cursor = if titles.len() as i64 > limit {
+        titles.truncate(limit as usize);
+        titles.last().map(|memo| MemoCursor::new(memo, query.sort).encode())
+    } else {
+        None
     };
+    let owner = db::get_user_by_name(db_pool, security).await?;
 
-    Ok(HttpResponse::Ok().json(MemoTitleList {
+    Ok(HttpResponse::Ok().json(MemoTitlePage {
         memos: titles,
         user: owner,
+        next_cursor: next_cursor,
     }))
 }
 
 #[derive(Deserialize)]
 pub struct SearchMemoQuery {
+    /// web search style: words, "phrases", -excluded, OR, prefix*
     pub search: Option<String>,
+    pub group_id: Option<i32>,
+    /// username of the memo owner
+    pub owner: Option<String>,
+    /// savetime range, both ends included
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    #[serde(default)]
+    pub scope: MemoScope,
+    /// also look in older revisions of the memos
+    #[serde(default)]
+    pub include_history: bool,
+    /// also look in the text of uploaded files
+    #[serde(default)]
+    pub include_attachments: bool,
 }
 
 #[post("/memo/search")]
@@ -95,35 +250,316 @@ pub async fn search_memo(
 ) -> Result<HttpResponse, OrganizatorError> {
     let query = qry.into_inner();
     debug!("Search memos with criteria {:#?}", &query.search);
+    run_search(db_pool.into_inner(), &query, security).await
+}
 
-    let mut titles = db::search_memo(db_pool.into_inner(), query, security).await?;
-    let owner_entry = titles.pop().unwrap();
-    let owner = User {
-        id: owner_entry.user_id,
-        username: owner_entry.title,
-    };
-    Ok(HttpResponse::Ok().json(MemoTitleList {
-        memos: titles,
+async fn run_search(
+    db_pool: Arc<Pool>,
+    query: &SearchMemoQuery,
+    security: Security,
+) -> Result<HttpResponse, OrganizatorError> {
+    let tsquery = search_query::to_tsquery(query.search.as_deref().unwrap_or(""))
+        .map_err(OrganizatorError::BadRequest)?;
+
+    let memos = db::search_memo(db_pool.clone(), query, &tsquery, security.clone()).await?;
+    let owner = db::get_user_by_name(db_pool, security).await?;
+    Ok(HttpResponse::Ok().json(MemoSearchList {
+        memos: memos,
         user: owner,
     }))
 }
 
+#[get("/saved_search/")]
+pub async fn get_saved_searches(
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let searches = db::get_saved_searches(db_pool.into_inner(), security).await?;
+    Ok(HttpResponse::Ok().json(SavedSearchList {
+        searches: searches,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct SavedSearchQuery {
+    pub name: String,
+}
+
+/// Saves the criteria of the search form under the name given in the url
+#[post("/saved_search/")]
+pub async fn save_search(
+    name: Query<SavedSearchQuery>,
+    qry: Form<SearchMemoQuery>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let name = name.into_inner().name;
+    let query = qry.into_inner();
+    if name.trim().is_empty() {
+        return Err(OrganizatorError::BadRequest("The saved search needs a name".to_string()));
+    }
+    // refuse what could never run
+    search_query::to_tsquery(query.search.as_deref().unwrap_or(""))
+        .map_err(OrganizatorError::BadRequest)?;
+
+    let saved = db::insert_saved_search(db_pool.into_inner(), name.trim(), &query, security).await?;
+    Ok(HttpResponse::Ok().json(saved))
+}
+
+#[get("/saved_search/{id}/run")]
+pub async fn run_saved_search(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let db_pool = db_pool.into_inner();
+    let saved = db::get_saved_search(db_pool.clone(), id.into_inner(), security.clone()).await?;
+    run_search(db_pool, &SearchMemoQuery::from(saved), security).await
+}
+
+#[delete("/saved_search/{id}")]
+pub async fn delete_saved_search(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    db::delete_saved_search(db_pool.into_inner(), id.into_inner(), security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Deserialize)]
+pub struct JumpToMemoQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+impl JumpToMemoQuery {
+    const DEFAULT_LIMIT: i64 = 10;
+    const MAX_LIMIT: i64 = 50;
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+}
+
+/// Type-ahead lookup of memo titles, forgiving typos and partial words
+#[get("/memo/jump")]
+pub async fn jump_to_memo(
+    qry: Query<JumpToMemoQuery>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let query = qry.into_inner();
+    if query.q.trim().is_empty() {
+        return Err(OrganizatorError::BadRequest("q has to contain part of a title".to_string()));
+    }
+    let memos = db::jump_to_memo(db_pool.into_inner(), &query, security).await?;
+    Ok(HttpResponse::Ok().json(MemoJumpList {
+        memos: memos,
+    }))
+}
+
 #[get("/memo/{id}")]
 pub async fn get_memo(
     id: actix_web::web::Path<i32>,
     security: Security,
     db_pool: Data<Pool>,
 ) -> Result<HttpResponse, OrganizatorError> {
-    let memo = db::get_memo(db_pool.into_inner(), id.into_inner(), security).await?;
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Read, security.clone()).await?;
+    let memo = db::get_memo(db_pool, id, security).await?;
     Ok(HttpResponse::Ok().json(memo))
 }
 
+/// What the requester can do with the memo, to show or hide the controls
+#[get("/memo/{id}/permissions")]
+pub async fn get_memo_permissions(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let level = permissions::access_level(db_pool.into_inner(), &Resource::Memo(id.into_inner()), security).await?;
+    Ok(HttpResponse::Ok().json(EffectivePermissions::from(level)))
+}
+
+#[get("/memo/{id}/share")]
+pub async fn get_memo_shares(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Share, security.clone()).await?;
+    let shares = db::memo_shares(&db_pool, security.get_user_name(), id).await?;
+    Ok(HttpResponse::Ok().json(MemoShareList {
+        shares: shares,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct MemoShareForm {
+    pub username: String,
+    /// 1 read, 2 write
+    pub access: i32,
+}
+
+/// Shares the memo alone with another user, or changes the access already given
+#[put("/memo/{id}/share")]
+pub async fn share_memo(
+    id: actix_web::web::Path<i32>,
+    form: Form<MemoShareForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    if form.access != 1 && form.access != 2 {
+        return Err(OrganizatorError::BadRequest("access has to be 1 (read) or 2 (write)".to_string()));
+    }
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Share, security.clone()).await?;
+    db::set_memo_share(&db_pool, security.get_user_name(), id, form.username.trim(), form.access).await?;
+    let shares = db::memo_shares(&db_pool, security.get_user_name(), id).await?;
+    Ok(HttpResponse::Ok().json(MemoShareList {
+        shares: shares,
+    }))
+}
+
+#[delete("/memo/{id}/share/{username}")]
+pub async fn unshare_memo(
+    path: actix_web::web::Path<(i32, String)>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let (id, username) = path.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Share, security.clone()).await?;
+    db::revoke_memo_share(&db_pool, security.get_user_name(), id, &username).await?;
+    let shares = db::memo_shares(&db_pool, security.get_user_name(), id).await?;
+    Ok(HttpResponse::Ok().json(MemoShareList {
+        shares: shares,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct TransferForm {
+    /// the new owner
+    pub username: String,
+}
+
+/// Only the owner or the admin can give the resource away
+async fn check_transfer(
+    db_pool: Arc<Pool>,
+    resource: Resource,
+    security: Security,
+) -> Result<(), OrganizatorError> {
+    let requester = db::get_user_by_name(db_pool.clone(), security.clone()).await?;
+    if requester.id != permissions::ADMIN_USER_ID {
+        permissions::check(db_pool, resource, Action::Transfer, security).await?;
+    }
+    Ok(())
+}
+
+#[post("/memo/{id}/transfer")]
+pub async fn transfer_memo(
+    id: actix_web::web::Path<i32>,
+    form: Form<TransferForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    check_transfer(db_pool.clone(), Resource::Memo(id), security.clone()).await?;
+    db::transfer_memo(db_pool, id, form.username.trim(), security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/memo/{id}/history")]
+pub async fn get_memo_history(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Read, security.clone()).await?;
+    let revisions = db::get_memo_history(db_pool, id, security).await?;
+    Ok(HttpResponse::Ok().json(MemoRevisionList {
+        revisions: revisions,
+    }))
+}
+
+#[get("/memo/{id}/history/{rev}")]
+pub async fn get_memo_revision(
+    path: actix_web::web::Path<(i32, i32)>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let (id, rev) = path.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Read, security.clone()).await?;
+    let revision = db::get_memo_revision(db_pool, id, rev, security).await?;
+    Ok(HttpResponse::Ok().json(revision))
+}
+
+#[post("/memo/{id}/history/{rev}/restore")]
+pub async fn restore_memo(
+    path: actix_web::web::Path<(i32, i32)>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let (id, rev) = path.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::EditText, security.clone()).await?;
+    let memo = db::restore_memo(db_pool, id, rev, security).await?;
+    Ok(HttpResponse::Ok().json(memo))
+}
+
+#[derive(Deserialize)]
+pub struct MemoDiffQuery {
+    pub from: i32,
+    /// revision to compare with, the current memo when missing
+    pub to: Option<i32>,
+    #[serde(default)]
+    pub granularity: Granularity,
+}
+
+#[get("/memo/{id}/diff")]
+pub async fn memo_diff(
+    id: actix_web::web::Path<i32>,
+    qry: Query<MemoDiffQuery>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let query = qry.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Read, security.clone()).await?;
+
+    let old = db::get_memo_revision(db_pool.clone(), id, query.from, security.clone()).await?;
+    let new_text = match query.to {
+        Some(rev) => db::get_memo_revision(db_pool, id, rev, security).await?.full_text(),
+        None => db::get_memo(db_pool, id, security).await?.memo.full_text(),
+    };
+
+    Ok(HttpResponse::Ok().json(MemoDiff {
+        memo_id: id,
+        from: query.from,
+        to: query.to,
+        granularity: query.granularity,
+        hunks: diff::text_diff(&old.full_text(), &new_text, query.granularity),
+    }))
+}
+
 #[derive(Deserialize)]
 pub struct MemoWrite {
     #[serde(alias = "memoId")]
     pub memo_id: Option<i32>,
     pub text: Option<String>,
     pub group_id: Option<i32>,
+    /// savetime of the memo the edit is based on, stale edits are rejected
+    pub savetime: Option<i64>,
 }
 #[post("/memo/")]
 pub async fn memo_write(
@@ -131,10 +567,172 @@ pub async fn memo_write(
     security: Security,
     db_pool: Data<Pool>,
 ) -> Result<HttpResponse, OrganizatorError> {
-    let memo = db::write_memo(db_pool.into_inner(), memo_write.into_inner(), security).await?;
+    let memo = save_memo(db_pool.into_inner(), memo_write.into_inner().into(), security).await?;
+    Ok(HttpResponse::Ok().json(memo))
+}
+
+#[derive(Deserialize)]
+pub struct MemoPut {
+    pub title: String,
+    pub text: String,
+    pub group_id: Option<i32>,
+    /// savetime of the memo the edit is based on, stale edits are rejected
+    pub savetime: Option<i64>,
+}
+
+#[put("/memo/{id}")]
+pub async fn memo_put(
+    id: actix_web::web::Path<i32>,
+    memo_put: Json<MemoPut>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let memo_put = memo_put.into_inner();
+    if memo_put.title.contains(|c| c == '\n' || c == '\r') {
+        return Err(OrganizatorError::BadRequest("The title has to be a single line".to_string()));
+    }
+    if memo_put.title.is_empty() && memo_put.text.is_empty() {
+        return Err(OrganizatorError::BadRequest("Use DELETE to remove a memo".to_string()));
+    }
+
+    let memo_save = MemoSave {
+        memo_id: Some(id.into_inner()),
+        title: memo_put.title,
+        memotext: db::stored_memotext(memo_put.text),
+        group_id: memo_put.group_id,
+        savetime: memo_put.savetime,
+    };
+    let memo = save_memo(db_pool.into_inner(), memo_save, security).await?;
+    Ok(HttpResponse::Ok().json(memo))
+}
+
+#[delete("/memo/{id}")]
+pub async fn memo_delete(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::Memo(id), Action::Delete, security.clone()).await?;
+    db::delete_memo(db_pool, id, security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Memo as handed to memo_write, the title apart from the rest of the text
+pub struct MemoSave {
+    pub memo_id: Option<i32>,
+    pub title: String,
+    pub memotext: String,
+    pub group_id: Option<i32>,
+    pub savetime: Option<i64>,
+}
+
+/// Writes the memo, an edit based on an older version gets merged with the one saved meanwhile.
+/// Answers 409 with the saved memo when there is nothing to merge against
+/// and 422 with the conflict markers when the edits overlap.
+async fn save_memo(
+    db_pool: Arc<Pool>,
+    memo: MemoSave,
+    security: Security,
+) -> Result<GetWriteMemo, OrganizatorError> {
+    match (memo.memo_id, memo.group_id) {
+        (Some(id), _) => {
+            let level = permissions::check(db_pool.clone(), Resource::Memo(id), Action::EditText, security.clone()).await?;
+            let saved = db::get_memo(db_pool.clone(), id, security.clone()).await?.memo;
+            if saved.title.as_deref().unwrap_or("") != memo.title {
+                permissions::require(level, &Resource::Memo(id), Action::EditTitle)?;
+            }
+            // like memo_write, a missing group_id leaves the group alone for anybody but the owner
+            let saved_group_id = saved.memogroup.map(|group| group.id);
+            if let Some(group_id) = memo.group_id.filter(|group_id| Some(*group_id) != saved_group_id) {
+                permissions::require(level, &Resource::Memo(id), Action::ChangeGroup)?;
+                permissions::check(db_pool.clone(), Resource::MemoGroup(group_id), Action::ChangeGroup, security.clone()).await?;
+            }
+        }
+        // a new memo can only go to a group of its owner
+        (None, Some(group_id)) => {
+            permissions::check(db_pool.clone(), Resource::MemoGroup(group_id), Action::ChangeGroup, security.clone()).await?;
+        }
+        (None, None) => {}
+    }
+    let err = match db::write_memo(db_pool.clone(), &memo, security.clone()).await {
+        Ok(memo) => return Ok(memo),
+        Err(err) => err,
+    };
+    let (id, base_savetime) = match (err.sql_state(), memo.memo_id, memo.savetime) {
+        (Some("40001"), Some(id), Some(savetime)) => (id, savetime),
+        _ => return Err(err),
+    };
+
+    let current = db::get_memo(db_pool.clone(), id, security.clone()).await?;
+    let base = match db::get_memo_revision_at(db_pool.clone(), id, base_savetime, security.clone()).await? {
+        Some(base) => base,
+        // without the version the client started from there is nothing to merge against
+        None => return Err(OrganizatorError::Conflict(current)),
+    };
+    let merge = merge::merge3(
+        &base.full_text(),
+        &current.memo.full_text(),
+        &memo.full_text(),
+    );
+    debug!("Merged concurrent edits of memo {}, {} conflicts", id, merge.conflicts);
+    if merge.conflicts > 0 {
+        return Err(OrganizatorError::MergeConflict(MergeConflict {
+            memo: current.memo,
+            user: current.user,
+            merged: merge.text,
+            conflicts: merge.conflicts,
+        }));
+    }
+
+    let merged = MemoSave::from(MemoWrite {
+        memo_id: Some(id),
+        text: Some(merge.text),
+        group_id: memo.group_id,
+        savetime: current.memo.savetime,
+    });
+    db::write_memo(db_pool, &merged, security).await
+}
+
+#[get("/trash/")]
+pub async fn get_trash(
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let memos = db::get_trash(db_pool.into_inner(), security).await?;
+    Ok(HttpResponse::Ok().json(TrashList {
+        memos: memos,
+    }))
+}
+
+#[post("/trash/{id}/restore")]
+pub async fn restore_from_trash(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::TrashedMemo(id), Action::Delete, security.clone()).await?;
+    db::restore_from_trash(&db_pool, id, &security).await?;
+    let memo = db::get_memo(db_pool, id, security).await?;
     Ok(HttpResponse::Ok().json(memo))
 }
 
+#[delete("/trash/{id}")]
+pub async fn purge_memo(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::TrashedMemo(id), Action::Delete, security.clone()).await?;
+    db::purge_memo(db_pool, id, security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
 #[get("/memogroup/")]
 pub async fn get_memo_group(
     security: Security,
@@ -146,6 +744,239 @@ pub async fn get_memo_group(
     }))
 }
 
+/// What the requester can do with the memos of the group
+#[get("/memogroup/{id}/permissions")]
+pub async fn get_memo_group_permissions(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let level = permissions::access_level(db_pool.into_inner(), &Resource::MemoGroup(id.into_inner()), security).await?;
+    Ok(HttpResponse::Ok().json(EffectivePermissions::from(level)))
+}
+
+#[post("/memogroup/{id}/transfer")]
+pub async fn transfer_memo_group(
+    id: actix_web::web::Path<i32>,
+    form: Form<TransferForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    check_transfer(db_pool.clone(), Resource::MemoGroup(id), security.clone()).await?;
+    db::transfer_memo_group(db_pool, id, form.username.trim(), security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Deserialize)]
+pub struct MemoGroupForm {
+    pub name: String,
+    /// when creating, the group to nest it in
+    pub parent_id: Option<i32>,
+}
+
+impl MemoGroupForm {
+    fn name(&self) -> Result<&str, OrganizatorError> {
+        let name = self.name.trim();
+        if name.is_empty() || name.contains(|c| c == '\n' || c == '\r') {
+            return Err(OrganizatorError::BadRequest("The memo group name has to be a single, non empty line".to_string()));
+        }
+        Ok(name)
+    }
+}
+
+#[post("/memogroup/")]
+pub async fn create_memo_group(
+    form: Form<MemoGroupForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let db_pool = db_pool.into_inner();
+    if let Some(parent_id) = form.parent_id {
+        permissions::check(db_pool.clone(), Resource::MemoGroup(parent_id), Action::ChangeGroup, security.clone()).await?;
+    }
+    let memo_group = db::insert_memo_group(db_pool, form.name()?, form.parent_id, security).await?;
+    Ok(HttpResponse::Ok().json(memo_group))
+}
+
+#[derive(Deserialize)]
+pub struct MoveMemoGroupForm {
+    /// missing to move it to the top level
+    pub parent_id: Option<i32>,
+}
+
+/// Nests the group in another one of the same owner, returns the new hierarchy
+#[put("/memogroup/{id}/parent")]
+pub async fn move_memo_group(
+    id: actix_web::web::Path<i32>,
+    form: Form<MoveMemoGroupForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::MemoGroup(id), Action::ChangeGroup, security.clone()).await?;
+    if let Some(parent_id) = form.parent_id {
+        permissions::check(db_pool.clone(), Resource::MemoGroup(parent_id), Action::ChangeGroup, security.clone()).await?;
+    }
+    db::move_memo_group(db_pool.clone(), id, form.parent_id, security.clone()).await?;
+    let memogroups = db::get_memo_groups(db_pool, security).await?;
+    Ok(HttpResponse::Ok().json(MemoGroupList {
+        memogroups: memogroups,
+    }))
+}
+
+#[put("/memogroup/{id}")]
+pub async fn rename_memo_group(
+    id: actix_web::web::Path<i32>,
+    form: Form<MemoGroupForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::MemoGroup(id), Action::EditTitle, security.clone()).await?;
+    let memo_group = db::rename_memo_group(db_pool, id, form.name()?, security).await?;
+    Ok(HttpResponse::Ok().json(memo_group))
+}
+
+#[derive(Deserialize)]
+pub struct DeleteMemoGroupQuery {
+    /// memo group of the same owner taking over the memos and files
+    pub reassign_to: Option<i32>,
+}
+
+#[delete("/memogroup/{id}")]
+pub async fn delete_memo_group(
+    id: actix_web::web::Path<i32>,
+    qry: Query<DeleteMemoGroupQuery>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::MemoGroup(id), Action::Delete, security.clone()).await?;
+    db::delete_memo_group(db_pool, id, qry.reassign_to, security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/usergroup/")]
+pub async fn get_user_groups(
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let usergroups = db::get_user_groups(db_pool.into_inner(), security).await?;
+    Ok(HttpResponse::Ok().json(UserGroupList {
+        usergroups: usergroups,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct UserGroupForm {
+    pub name: String,
+}
+
+#[post("/usergroup/")]
+pub async fn create_user_group(
+    form: Form<UserGroupForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let name = form.name.trim();
+    if name.is_empty() || name.contains(|c| c == '\n' || c == '\r') {
+        return Err(OrganizatorError::BadRequest("The user group name has to be a single, non empty line".to_string()));
+    }
+    let usergroup = db::insert_user_group(db_pool.into_inner(), name, security).await?;
+    Ok(HttpResponse::Ok().json(usergroup))
+}
+
+#[get("/usergroup/{id}")]
+pub async fn get_user_group(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let usergroup = db::get_user_group(db_pool.into_inner(), id.into_inner(), security).await?;
+    Ok(HttpResponse::Ok().json(usergroup))
+}
+
+#[delete("/usergroup/{id}")]
+pub async fn delete_user_group(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    db::delete_user_group(db_pool.into_inner(), id.into_inner(), security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Deserialize)]
+pub struct UserGroupMemberForm {
+    pub username: String,
+}
+
+/// Invites the user to join the group, returns the members and pending invitations
+#[post("/usergroup/{id}/invitation")]
+pub async fn invite_user_group_member(
+    id: actix_web::web::Path<i32>,
+    form: Form<UserGroupMemberForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    db::invite_user_group_member(db_pool.clone(), id, form.username.trim(), security.clone()).await?;
+    let usergroup = db::get_user_group(db_pool, id, security).await?;
+    Ok(HttpResponse::Ok().json(usergroup))
+}
+
+/// Invitations to user groups waiting for an answer, with the memo groups they give access to
+#[get("/invitation/")]
+pub async fn get_invitations(
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let invitations = db::get_pending_invitations(db_pool.into_inner(), security).await?;
+    Ok(HttpResponse::Ok().json(PendingInvitationList {
+        invitations: invitations,
+    }))
+}
+
+#[post("/invitation/{id}/accept")]
+pub async fn accept_invitation(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    db::answer_invitation(db_pool.into_inner(), id.into_inner(), true, security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/invitation/{id}/decline")]
+pub async fn decline_invitation(
+    id: actix_web::web::Path<i32>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    db::answer_invitation(db_pool.into_inner(), id.into_inner(), false, security).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Removes the user from the group, returns the members left
+#[delete("/usergroup/{id}/member/{username}")]
+pub async fn remove_user_group_member(
+    path: actix_web::web::Path<(i32, String)>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let (id, username) = path.into_inner();
+    let db_pool = db_pool.into_inner();
+    db::remove_user_group_member(db_pool.clone(), id, &username, security.clone()).await?;
+    let usergroup = db::get_user_group(db_pool, id, security).await?;
+    Ok(HttpResponse::Ok().json(usergroup))
+}
+
 #[derive(Deserialize, Debug)]
 pub struct LoginQuery {
     pub j_username: Option<String>,
@@ -208,7 +1039,7 @@ pub async fn change_password(
         return Ok(HttpResponse::BadRequest().finish());
     }
     // Only root can change the password of another user
-    if user_login.id != 1 && change_password_form.username.is_some() {
+    if user_login.id != permissions::ADMIN_USER_ID && change_password_form.username.is_some() {
         return Ok(HttpResponse::BadRequest().finish());
     }
 
@@ -228,11 +1059,88 @@ pub async fn change_password(
         &mut salt,
         &mut pbkdf2_hash,
     )?;
-    db::update_password(&db_pool, target_username, &salt, &pbkdf2_hash).await?;
+    db::update_password(&db_pool, security.get_user_name(), target_username, &salt, &pbkdf2_hash).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// Text search configurations of PostgreSQL a user can choose from
+const SEARCH_CONFIGS: &[&str] = &[
+    "simple", "danish", "dutch", "english", "finnish", "french", "german", "hungarian", "italian",
+    "norwegian", "portuguese", "romanian", "russian", "spanish", "swedish", "turkish",
+];
+
+#[derive(Deserialize)]
+pub struct SearchConfigQuery {
+    /// missing or empty to use the deployment default
+    pub search_config: Option<String>,
+}
+
+impl SearchConfigQuery {
+    fn validate(&self) -> bool {
+        match &self.search_config {
+            Some(config) => SEARCH_CONFIGS.contains(&config.as_str()),
+            None => true,
+        }
+    }
+}
+
+#[get("/search_config")]
+pub async fn get_search_config(
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let search_config = db::get_search_config(db_pool.into_inner(), security).await?;
+    Ok(HttpResponse::Ok().json(search_config))
+}
+
+#[put("/search_config")]
+pub async fn put_search_config(
+    search_config_form: Form<SearchConfigQuery>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let mut search_config_form = search_config_form.into_inner();
+    search_config_form.search_config = search_config_form.search_config.filter(|config| !config.is_empty());
+    if !search_config_form.validate() {
+        return Err(OrganizatorError::BadRequest(format!(
+            "search_config must be one of: {}",
+            SEARCH_CONFIGS.join(", ")
+        )));
+    }
+    let db_pool = db_pool.into_inner();
+    db::update_search_config(db_pool.clone(), &search_config_form.search_config, security.clone()).await?;
+    let search_config = db::get_search_config(db_pool, security).await?;
+    Ok(HttpResponse::Ok().json(search_config))
+}
+
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub memo_group_id: Option<i32>,
+    pub user_group_id: Option<i32>,
+    /// username of who made the change or whom it is about
+    pub user: Option<String>,
+    /// changed_on range, both ends included
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+/// Changes of access rights, users see the ones to what they owned at the time
+#[get("/audit/")]
+pub async fn get_audit_log(
+    qry: Query<AuditQuery>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let db_pool = db_pool.into_inner();
+    let requester = db::get_user_by_name(db_pool.clone(), security.clone()).await?;
+    let is_admin = requester.id == permissions::ADMIN_USER_ID;
+    let entries = db::get_audit_log(db_pool, &qry.into_inner(), is_admin, security).await?;
+    Ok(HttpResponse::Ok().json(AuditLog {
+        entries: entries,
+    }))
+}
+
 #[get("/version")]
 pub async fn version() -> Result<HttpResponse, OrganizatorError> {
     Ok(HttpResponse::Ok()
@@ -310,10 +1218,15 @@ pub async fn upload_file(
         // if let Some(filename) = content_type.get_filename() {
         match content_type.get_filename() {
             Some(filename) => {
+                if let Some(group_id) = memo_group_id {
+                    permissions::check(db_pool.clone(), Resource::MemoGroup(group_id), Action::Upload, security.clone()).await?;
+                }
                 // extract the extension
                 let file_uuid = Uuid::new_v4();
                 let ext = extension(&filename);
                 let filepath = format!("{}/{}{}", file_upload_config.dir, file_uuid, ext.unwrap_or(""));
+                let text_path = filepath.clone();
+                let text_ext = ext.map(String::from);
                 res = FileUpload { filename: format!("{}{}", file_uuid, ext.unwrap_or("")) };
                 processed = true;
 
@@ -328,9 +1241,14 @@ pub async fn upload_file(
                     // filesystem operations are blocking, we have to use threadpool
                     f = web::block(move || f.write_all(&data).map(|_| f)).await?;
                 }
+                drop(f);
+                // text for the search, reading the file is blocking as well
+                let content_text = web::block(move || {
+                    Ok::<_, std::io::Error>(file_text::extract_text(&text_path, text_ext.as_deref()))
+                }).await?;
                 debug!("memo_group_id for file: {:#?}", &memo_group_id);
                 // add the database entry
-                db::insert_filestore(&db_pool, &file_uuid, security.get_user_name(), &filename, &memo_group_id).await?;
+                db::insert_filestore(&db_pool, &file_uuid, security.get_user_name(), &filename, &memo_group_id, &content_text).await?;
             }
             None => {
                 let mut val = String::with_capacity(20);
@@ -369,7 +1287,7 @@ pub async fn file_auth(
     let filename = without_extension(uri);
 
     let uuid = Uuid::from_str(&filename).unwrap();
-    let _permissions = db::file_permissions(&db_pool.into_inner(), &uuid, security.get_user_name(), Some(1)).await?;
+    permissions::check(db_pool.into_inner(), Resource::File(uuid), Action::Read, security).await?;
     Ok(HttpResponse::NoContent().finish())
     //Ok(HttpResponse::NoContent().finish())
     //Ok(HttpResponse::Unauthorized().finish())
@@ -381,6 +1299,49 @@ pub async fn explicit_permissions(
     security: Security,
     db_pool: Data<Pool>,
 ) -> Result<HttpResponse, OrganizatorError> {
-    let permissions = db::explicit_permissions(&db_pool.into_inner(), security.get_user_name(), id.into_inner()).await?;
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::MemoGroup(id), Action::Share, security.clone()).await?;
+    let permissions = db::explicit_permissions(&db_pool, security.get_user_name(), id).await?;
+    Ok(HttpResponse::Ok().json(permissions))
+}
+
+#[derive(Deserialize)]
+pub struct ExplicitPermissionForm {
+    pub user_group_id: i32,
+    /// 1 read, 2 write
+    pub access: i32,
+}
+
+/// Shares the memo group with the user group or changes the access already given
+#[put("/explicit_permissions/{id}")]
+pub async fn grant_explicit_permission(
+    id: actix_web::web::Path<i32>,
+    form: Form<ExplicitPermissionForm>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    if form.access != 1 && form.access != 2 {
+        return Err(OrganizatorError::BadRequest("access has to be 1 (read) or 2 (write)".to_string()));
+    }
+    let id = id.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::MemoGroup(id), Action::Share, security.clone()).await?;
+    db::set_memo_acl(&db_pool, security.get_user_name(), id, form.user_group_id, form.access).await?;
+    let permissions = db::explicit_permissions(&db_pool, security.get_user_name(), id).await?;
+    Ok(HttpResponse::Ok().json(permissions))
+}
+
+#[delete("/explicit_permissions/{id}/{user_group_id}")]
+pub async fn revoke_explicit_permission(
+    path: actix_web::web::Path<(i32, i32)>,
+    security: Security,
+    db_pool: Data<Pool>,
+) -> Result<HttpResponse, OrganizatorError> {
+    let (id, user_group_id) = path.into_inner();
+    let db_pool = db_pool.into_inner();
+    permissions::check(db_pool.clone(), Resource::MemoGroup(id), Action::Share, security.clone()).await?;
+    db::revoke_memo_acl(&db_pool, security.get_user_name(), id, user_group_id).await?;
+    let permissions = db::explicit_permissions(&db_pool, security.get_user_name(), id).await?;
     Ok(HttpResponse::Ok().json(permissions))
-}
\ No newline at end of file
+}
@@ -0,0 +1,187 @@
+/*
+ * Web search style queries translated to the to_tsquery syntax:
+ *   words       all have to match
+ *   "a phrase"  words next to each other
+ *   -word       excluded, also -"a phrase"
+ *   a OR b      either of them
+ *   pre*        prefix match
+ * Anything that is not a letter or a digit separates words, so the result is always valid.
+ */
+
+enum Token {
+    Or,
+    Term(Term),
+}
+
+struct Term {
+    words: Vec<String>,
+    prefix: bool,
+    negated: bool,
+}
+
+impl Term {
+    fn new(text: &str, prefix: bool, negated: bool) -> Self {
+        Term {
+            words: text
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(String::from)
+                .collect(),
+            prefix,
+            negated,
+        }
+    }
+
+    fn render(&self) -> Option<String> {
+        if self.words.is_empty() {
+            return None;
+        }
+        let mut rendered = self.words.join(" <-> ");
+        if self.prefix {
+            rendered.push_str(":*");
+        }
+        if self.words.len() > 1 {
+            rendered = format!("({})", rendered);
+        }
+        if self.negated {
+            rendered.insert(0, '!');
+        }
+        Some(rendered)
+    }
+}
+
+fn lex(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.peek().map_or(false, |c| c.is_whitespace()) {
+            chars.next();
+        }
+        let negated = match chars.peek() {
+            None => break,
+            Some('-') => {
+                chars.next();
+                true
+            }
+            Some(_) => false,
+        };
+
+        let mut text = String::new();
+        if chars.peek() == Some(&'"') {
+            // phrase, an unbalanced quote runs to the end
+            chars.next();
+            for c in &mut chars {
+                if c == '"' {
+                    break;
+                }
+                text.push(c);
+            }
+            tokens.push(Token::Term(Term::new(&text, false, negated)));
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            if text == "OR" && !negated {
+                tokens.push(Token::Or);
+            } else {
+                tokens.push(Token::Term(Term::new(&text, text.ends_with('*'), negated)));
+            }
+        }
+    }
+    tokens
+}
+
+/// Translates the query, the error explains why there's nothing to search for
+pub fn to_tsquery(input: &str) -> Result<String, String> {
+    // terms joined by OR make a group, all groups have to match
+    let mut groups: Vec<Vec<&Term>> = Vec::new();
+    let mut pending_or = false;
+    let tokens = lex(input);
+    for token in &tokens {
+        match token {
+            Token::Or => pending_or = !groups.is_empty(),
+            Token::Term(term) if term.words.is_empty() => {}
+            Token::Term(term) => {
+                match groups.last_mut() {
+                    Some(group) if pending_or => group.push(term),
+                    _ => groups.push(vec![term]),
+                }
+                pending_or = false;
+            }
+        }
+    }
+
+    if groups.is_empty() {
+        return Err("The search has no words to look for".to_string());
+    }
+    if groups.iter().all(|group| group.iter().all(|term| term.negated)) {
+        return Err("The search needs at least one word that is not excluded".to_string());
+    }
+
+    Ok(groups
+        .iter()
+        .map(|group| {
+            let terms: Vec<String> = group.iter().filter_map(|term| term.render()).collect();
+            if terms.len() > 1 {
+                format!("({})", terms.join(" | "))
+            } else {
+                terms.join("")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" & "))
+}
+
+#[cfg(test)]
+mod test_search_query {
+    use super::to_tsquery;
+
+    #[test]
+    fn words() {
+        assert_eq!(to_tsquery("foo bar"), Ok("foo & bar".to_string()));
+        assert_eq!(to_tsquery("  ăla   bală "), Ok("ăla & bală".to_string()));
+    }
+
+    #[test]
+    fn phrases() {
+        assert_eq!(to_tsquery("\"foo bar\" baz"), Ok("(foo <-> bar) & baz".to_string()));
+        assert_eq!(to_tsquery("baz \"foo bar"), Ok("baz & (foo <-> bar)".to_string()));
+        assert_eq!(to_tsquery("e-mail"), Ok("(e <-> mail)".to_string()));
+    }
+
+    #[test]
+    fn exclusions() {
+        assert_eq!(to_tsquery("foo -bar"), Ok("foo & !bar".to_string()));
+        assert_eq!(to_tsquery("foo -\"bar baz\""), Ok("foo & !(bar <-> baz)".to_string()));
+    }
+
+    #[test]
+    fn or() {
+        assert_eq!(to_tsquery("foo bar OR baz"), Ok("foo & (bar | baz)".to_string()));
+        assert_eq!(to_tsquery("OR foo OR"), Ok("foo".to_string()));
+        assert_eq!(to_tsquery("foo or bar"), Ok("foo & or & bar".to_string()));
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(to_tsquery("memo*"), Ok("memo:*".to_string()));
+        assert_eq!(to_tsquery("-memo* x"), Ok("!memo:* & x".to_string()));
+    }
+
+    #[test]
+    fn tsquery_syntax_is_stripped() {
+        assert_eq!(to_tsquery("foo & (bar | !baz):*"), Ok("foo & bar & baz:*".to_string()));
+        assert_eq!(to_tsquery("it's"), Ok("(it <-> s)".to_string()));
+    }
+
+    #[test]
+    fn nothing_to_search() {
+        assert!(to_tsquery("").is_err());
+        assert!(to_tsquery("\" & ! -").is_err());
+        assert!(to_tsquery("-foo -bar").is_err());
+    }
+}
@@ -0,0 +1,118 @@
+DROP FUNCTION memo_access;
+DROP FUNCTION file_access;
+DROP FUNCTION memo_group_access;
+DROP FUNCTION memo_group_acl;
+DROP FUNCTION access_read;
+DROP FUNCTION access_write;
+DROP FUNCTION access_owner;
+/*
+
+Access level of a user on a memo, memo group or uploaded file, as the
+permissions module of the server reads them:
+  0 none, 1 read, 2 write (the levels of memo_acl), 10 owner
+Unlike memo_read and memo_write they don't raise when the user has no access,
+only when the user or the object doesn't exist.
+The levels are named by access_read, access_write and access_owner, the same
+as permissions::AccessLevel, the other functions use them instead of the numbers.
+
+*/
+
+CREATE OR REPLACE FUNCTION access_read() RETURNS int AS $$ SELECT 1 $$ LANGUAGE 'sql' IMMUTABLE;
+CREATE OR REPLACE FUNCTION access_write() RETURNS int AS $$ SELECT 2 $$ LANGUAGE 'sql' IMMUTABLE;
+CREATE OR REPLACE FUNCTION access_owner() RETURNS int AS $$ SELECT 10 $$ LANGUAGE 'sql' IMMUTABLE;
+
+-- highest level given to the user through the user groups he is in, 0 without any.
+-- the acl can come from an ancestor of the memo group, see memo_group_tree_function.sql
+CREATE OR REPLACE FUNCTION memo_group_acl(p_memo_group_id memo_group.id%TYPE, p_user_id users.id%TYPE)
+  RETURNS int
+  AS $$
+    SELECT COALESCE(MAX(entries.o_access), 0)
+      FROM memo_group_acl_entries(p_memo_group_id) AS entries
+      JOIN user_group_detail ON user_group_detail.user_group_id = entries.o_user_group_id
+     WHERE user_group_detail.user_id = p_user_id;
+  $$
+LANGUAGE 'sql' STABLE;
+
+CREATE OR REPLACE FUNCTION memo_group_access(p_memo_group_id memo_group.id%TYPE, p_username users.username%TYPE)
+  RETURNS int
+  AS $$
+  DECLARE
+    v_requester_id users.id%TYPE;
+    v_owner_id     users.id%TYPE;
+  BEGIN
+    SELECT users.id INTO v_requester_id FROM users WHERE users.username = p_username;
+    IF v_requester_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_username USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END IF;
+
+    SELECT memo_group.user_id INTO v_owner_id FROM memo_group WHERE memo_group.id = p_memo_group_id;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'memo group % not found', p_memo_group_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    IF v_owner_id = v_requester_id THEN
+      RETURN access_owner();
+    END IF;
+    RETURN memo_group_acl(p_memo_group_id, v_requester_id);
+  END; $$
+LANGUAGE 'plpgsql' STABLE;
+
+-- p_in_trash looks for the memo in the trash instead, for restoring and purging it
+CREATE OR REPLACE FUNCTION memo_access(p_memo_id memo.id%TYPE, p_username users.username%TYPE, p_in_trash boolean DEFAULT false)
+  RETURNS int
+  AS $$
+  DECLARE
+    v_requester_id  users.id%TYPE;
+    v_owner_id      users.id%TYPE;
+    v_memo_group_id memo.group_id%TYPE;
+  BEGIN
+    SELECT users.id INTO v_requester_id FROM users WHERE users.username = p_username;
+    IF v_requester_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_username USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END IF;
+
+    SELECT memo.user_id, memo.group_id INTO v_owner_id, v_memo_group_id
+      FROM memo
+     WHERE memo.id = p_memo_id
+       AND (memo.deleted_on IS NOT NULL) = p_in_trash;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'No memo with id %', p_memo_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    IF v_owner_id = v_requester_id THEN
+      RETURN access_owner();
+    END IF;
+    -- shared through the memo group or on its own
+    RETURN GREATEST(memo_group_acl(v_memo_group_id, v_requester_id), memo_share_access(p_memo_id, v_requester_id));
+  END; $$
+LANGUAGE 'plpgsql' STABLE;
+
+CREATE OR REPLACE FUNCTION file_access(p_file_id filestore.id%TYPE, p_username users.username%TYPE)
+  RETURNS int
+  AS $$
+  DECLARE
+    v_requester_id  users.id%TYPE;
+    v_owner_id      users.id%TYPE;
+    v_memo_group_id filestore.memo_group_id%TYPE;
+  BEGIN
+    SELECT users.id INTO v_requester_id FROM users WHERE users.username = p_username;
+    IF v_requester_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_username USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END IF;
+
+    SELECT filestore.user_id, filestore.memo_group_id INTO v_owner_id, v_memo_group_id
+      FROM filestore
+     WHERE filestore.id = p_file_id;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'file % not found', p_file_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    IF v_owner_id = v_requester_id THEN
+      RETURN access_owner();
+    END IF;
+    IF v_memo_group_id IS NULL THEN
+      RETURN 0;
+    END IF;
+    RETURN memo_group_acl(v_memo_group_id, v_requester_id);
+  END; $$
+LANGUAGE 'plpgsql' STABLE;
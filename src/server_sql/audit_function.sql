@@ -0,0 +1,136 @@
+DROP TRIGGER audit_memo_acl_trigger ON memo_acl;
+DROP TRIGGER audit_memo_share_trigger ON memo_share;
+DROP TRIGGER audit_user_group_detail_trigger ON user_group_detail;
+DROP TRIGGER audit_memo_group_owner_trigger ON memo_group;
+DROP TRIGGER audit_memo_owner_trigger ON memo;
+DROP TRIGGER audit_password_trigger ON users;
+DROP TRIGGER audit_log_append_only_trigger ON audit_log;
+DROP FUNCTION audit_record;
+DROP FUNCTION audit_append_only;
+DROP FUNCTION audit_actor;
+DROP FUNCTION user_password_set;
+/*
+
+Audit log of the changes to access rights: memo_acl and memo_share entries, user group members,
+owners of memo groups and memos and passwords.
+The functions changing them name the user doing it with audit_actor, the triggers
+record it in audit_log with the row before and after the change and the owner
+of the object at that time, who keeps seeing it after a transfer or a delete.
+Password hashes are never recorded.
+
+*/
+
+-- the user making the changes for the rest of the transaction
+CREATE OR REPLACE FUNCTION audit_actor(p_username users.username%TYPE)
+  RETURNS void
+  AS $$
+    SELECT set_config('organizator.username', p_username, true);
+  $$
+LANGUAGE 'sql';
+
+CREATE OR REPLACE FUNCTION audit_record()
+  RETURNS trigger
+  AS $$
+  DECLARE
+    v_actor_id users.id%TYPE;
+    v_row      record;
+  BEGIN
+    SELECT users.id INTO v_actor_id FROM users WHERE users.username = current_setting('organizator.username', true);
+    IF TG_OP = 'DELETE' THEN
+      v_row := OLD;
+    ELSE
+      v_row := NEW;
+    END IF;
+
+    IF TG_TABLE_NAME = 'memo_acl' THEN
+      INSERT INTO audit_log (event, changed_on, actor_id, memo_group_id, user_group_id, owner_id, before, after)
+      VALUES ('acl', (extract(epoch FROM clock_timestamp()) * 1000)::int8, v_actor_id,
+              v_row.memo_group_id, v_row.user_group_id,
+              (SELECT memo_group.user_id FROM memo_group WHERE memo_group.id = v_row.memo_group_id),
+              CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END,
+              CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END);
+    ELSIF TG_TABLE_NAME = 'memo_share' THEN
+      -- under the group of the memo, like the memo_acl entries
+      INSERT INTO audit_log (event, changed_on, actor_id, memo_group_id, user_id, owner_id, before, after)
+      VALUES ('share', (extract(epoch FROM clock_timestamp()) * 1000)::int8, v_actor_id,
+              (SELECT memo.group_id FROM memo WHERE memo.id = v_row.memo_id), v_row.user_id,
+              (SELECT memo.user_id FROM memo WHERE memo.id = v_row.memo_id),
+              CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END,
+              CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END);
+    ELSIF TG_TABLE_NAME = 'user_group_detail' THEN
+      INSERT INTO audit_log (event, changed_on, actor_id, user_group_id, user_id, owner_id, before, after)
+      VALUES ('membership', (extract(epoch FROM clock_timestamp()) * 1000)::int8, v_actor_id,
+              v_row.user_group_id, v_row.user_id,
+              (SELECT user_group.user_id FROM user_group WHERE user_group.id = v_row.user_group_id),
+              CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END,
+              CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END);
+    ELSIF TG_TABLE_NAME = 'memo_group' THEN
+      INSERT INTO audit_log (event, changed_on, actor_id, memo_group_id, user_id, owner_id, before, after)
+      VALUES ('ownership', (extract(epoch FROM clock_timestamp()) * 1000)::int8, v_actor_id,
+              NEW.id, NEW.user_id, OLD.user_id,
+              jsonb_build_object('user_id', OLD.user_id),
+              jsonb_build_object('user_id', NEW.user_id));
+    ELSIF TG_TABLE_NAME = 'memo' THEN
+      -- under the group the memo was in, a transferred memo leaves it
+      INSERT INTO audit_log (event, changed_on, actor_id, memo_group_id, user_id, owner_id, before, after)
+      VALUES ('ownership', (extract(epoch FROM clock_timestamp()) * 1000)::int8, v_actor_id,
+              OLD.group_id, NEW.user_id, OLD.user_id,
+              jsonb_build_object('memo_id', OLD.id, 'user_id', OLD.user_id, 'group_id', OLD.group_id),
+              jsonb_build_object('memo_id', NEW.id, 'user_id', NEW.user_id, 'group_id', NEW.group_id));
+    ELSIF TG_TABLE_NAME = 'users' THEN
+      INSERT INTO audit_log (event, changed_on, actor_id, user_id, owner_id)
+      VALUES ('password', (extract(epoch FROM clock_timestamp()) * 1000)::int8, v_actor_id, NEW.id, NEW.id);
+    END IF;
+    RETURN NULL;
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE TRIGGER audit_memo_acl_trigger
+  AFTER INSERT OR UPDATE OR DELETE ON memo_acl
+  FOR EACH ROW EXECUTE PROCEDURE audit_record();
+
+CREATE TRIGGER audit_memo_share_trigger
+  AFTER INSERT OR UPDATE OR DELETE ON memo_share
+  FOR EACH ROW EXECUTE PROCEDURE audit_record();
+
+CREATE TRIGGER audit_user_group_detail_trigger
+  AFTER INSERT OR UPDATE OR DELETE ON user_group_detail
+  FOR EACH ROW EXECUTE PROCEDURE audit_record();
+
+CREATE TRIGGER audit_memo_group_owner_trigger
+  AFTER UPDATE OF user_id ON memo_group
+  FOR EACH ROW WHEN (OLD.user_id IS DISTINCT FROM NEW.user_id) EXECUTE PROCEDURE audit_record();
+
+CREATE TRIGGER audit_memo_owner_trigger
+  AFTER UPDATE OF user_id ON memo
+  FOR EACH ROW WHEN (OLD.user_id IS DISTINCT FROM NEW.user_id) EXECUTE PROCEDURE audit_record();
+
+CREATE TRIGGER audit_password_trigger
+  AFTER UPDATE OF pbkdf2 ON users
+  FOR EACH ROW EXECUTE PROCEDURE audit_record();
+
+CREATE OR REPLACE FUNCTION audit_append_only()
+  RETURNS trigger
+  AS $$
+  BEGIN
+    RAISE EXCEPTION 'audit_log can only be appended to' USING ERRCODE = '2F002'; -- modifying_sql_data_not_permitted
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE TRIGGER audit_log_append_only_trigger
+  BEFORE UPDATE OR DELETE OR TRUNCATE ON audit_log
+  FOR EACH STATEMENT EXECUTE PROCEDURE audit_append_only();
+
+-- p_username changes the password of p_target, himself or as the admin
+CREATE OR REPLACE FUNCTION user_password_set(
+  p_username users.username%TYPE,
+  p_target   users.username%TYPE,
+  p_salt     users.salt%TYPE,
+  p_pbkdf2   users.pbkdf2%TYPE)
+  RETURNS void
+  AS $$
+  BEGIN
+    PERFORM audit_actor(p_username);
+    UPDATE users SET salt = p_salt, pbkdf2 = p_pbkdf2 WHERE users.username = p_target;
+  END; $$
+LANGUAGE 'plpgsql';
@@ -2,4 +2,6 @@
 # CREATE EXTENSION unaccent;
 # ALTER FUNCTION unaccent(text) IMMUTABLE;
 
-CREATE INDEX memo_fulltext_idx ON memo USING gin(to_ts_vector(unaccent (title || memotext)));
\ No newline at end of file
+# memo.tsv is maintained by memo_tsv_trigger, see search_config_function.sql
+DROP INDEX IF EXISTS memo_fulltext_idx;
+CREATE INDEX memo_tsv_idx ON memo USING gin(tsv);
@@ -0,0 +1,5 @@
+# Run this as psql
+# CREATE EXTENSION pg_trgm;
+# unaccent has to be IMMUTABLE, see create_full_text_index.sql
+
+CREATE INDEX memo_title_trgm_idx ON memo USING gin(unaccent(title) gin_trgm_ops);
@@ -0,0 +1,86 @@
+DROP FUNCTION memo_acl_set;
+DROP FUNCTION memo_acl_revoke;
+DROP FUNCTION memo_group_owner;
+/*
+
+Sharing of a memo group with a user group through memo_acl.
+Levels as checked by memo_group_user_access: 1 read, 2 write.
+Only the owner of the memo group can change them. Subgroups without entries of
+their own are shared the same way, see memo_group_tree_function.sql.
+
+*/
+
+-- id of the owner, raises when the group doesn't exist or belongs to somebody else
+CREATE OR REPLACE FUNCTION memo_group_owner(p_memo_group_id memo_group.id%TYPE, p_username users.username%TYPE)
+  RETURNS users.id%TYPE
+  AS $$
+  DECLARE
+    v_owner_id     users.id%TYPE;
+    v_requester_id users.id%TYPE;
+  BEGIN
+    SELECT users.id INTO v_requester_id FROM users WHERE users.username = p_username;
+    IF v_requester_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_username USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END IF;
+
+    SELECT memo_group.user_id INTO v_owner_id FROM memo_group WHERE memo_group.id = p_memo_group_id FOR UPDATE;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'memo group % not found', p_memo_group_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+    IF v_owner_id <> v_requester_id THEN
+      RAISE EXCEPTION 'user % does not own memo group %', p_username, p_memo_group_id
+        USING ERRCODE = '2F004'; -- reading_sql_data_not_permitted
+    END IF;
+
+    RETURN v_owner_id;
+  END; $$
+LANGUAGE 'plpgsql';
+
+-- grants the access or changes the one already granted
+CREATE OR REPLACE FUNCTION memo_acl_set(
+  p_memo_group_id memo_group.id%TYPE,
+  p_username      users.username%TYPE,
+  p_user_group_id user_group.id%TYPE,
+  p_access        memo_acl.access%TYPE)
+  RETURNS void
+  AS $$
+  BEGIN
+    PERFORM audit_actor(p_username);
+    PERFORM memo_group_owner(p_memo_group_id, p_username);
+
+    IF p_access NOT IN (access_read(), access_write()) THEN
+      RAISE EXCEPTION 'access % is neither read (1) nor write (2)', p_access USING ERRCODE = '22023'; -- invalid_parameter_value
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM user_group WHERE user_group.id = p_user_group_id) THEN
+      RAISE EXCEPTION 'user group % not found', p_user_group_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    UPDATE memo_acl SET access = p_access
+     WHERE memo_acl.memo_group_id = p_memo_group_id
+       AND memo_acl.user_group_id = p_user_group_id;
+    IF NOT FOUND THEN
+      INSERT INTO memo_acl (memo_group_id, user_group_id, access)
+      VALUES (p_memo_group_id, p_user_group_id, p_access);
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION memo_acl_revoke(
+  p_memo_group_id memo_group.id%TYPE,
+  p_username      users.username%TYPE,
+  p_user_group_id user_group.id%TYPE)
+  RETURNS void
+  AS $$
+  BEGIN
+    PERFORM audit_actor(p_username);
+    PERFORM memo_group_owner(p_memo_group_id, p_username);
+
+    DELETE FROM memo_acl
+     WHERE memo_acl.memo_group_id = p_memo_group_id
+       AND memo_acl.user_group_id = p_user_group_id;
+    IF NOT FOUND THEN
+      RAISE EXCEPTION 'memo group % is not shared with user group %', p_memo_group_id, p_user_group_id
+        USING ERRCODE = '02000'; -- no_data
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
@@ -0,0 +1,56 @@
+DROP FUNCTION memo_group_delete;
+/*
+
+Deletes a memo group of the user.
+Memos (trashed ones too) and uploaded files still in the group are moved to
+p_reassign_to, another group of the same user; without it the delete is refused.
+The acl entries of the group go with it.
+
+*/
+
+CREATE OR REPLACE FUNCTION memo_group_delete(
+  p_username      users.username%TYPE,
+  p_memo_group_id memo_group.id%TYPE,
+  p_reassign_to   memo_group.id%TYPE)
+  RETURNS void
+  AS $$
+  DECLARE
+    v_user_id users.id%TYPE;
+  BEGIN
+    PERFORM audit_actor(p_username);
+    SELECT users.id INTO v_user_id
+      FROM memo_group
+      JOIN users ON memo_group.user_id = users.id
+     WHERE memo_group.id = p_memo_group_id
+       AND users.username = p_username
+       FOR UPDATE OF memo_group;
+
+    IF v_user_id IS NULL THEN
+      RAISE EXCEPTION 'memo group % not found', p_memo_group_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    IF p_reassign_to IS NOT NULL THEN
+      IF p_reassign_to = p_memo_group_id OR NOT EXISTS (
+        SELECT 1 FROM memo_group WHERE memo_group.id = p_reassign_to AND memo_group.user_id = v_user_id) THEN
+        RAISE EXCEPTION 'memo group % can not take over memo group %', p_reassign_to, p_memo_group_id
+          USING ERRCODE = '22023'; -- invalid_parameter_value
+      END IF;
+
+      UPDATE memo SET group_id = p_reassign_to WHERE memo.group_id = p_memo_group_id;
+      UPDATE filestore SET memo_group_id = p_reassign_to WHERE filestore.memo_group_id = p_memo_group_id;
+      UPDATE saved_search SET group_id = p_reassign_to WHERE saved_search.group_id = p_memo_group_id;
+    ELSIF EXISTS (SELECT 1 FROM memo WHERE memo.group_id = p_memo_group_id)
+       OR EXISTS (SELECT 1 FROM filestore WHERE filestore.memo_group_id = p_memo_group_id) THEN
+      RAISE EXCEPTION 'memo group % still has memos or files', p_memo_group_id
+        USING ERRCODE = '23503'; -- foreign_key_violation
+    END IF;
+
+    -- subgroups move up a level
+    UPDATE memo_group SET parent_id = (SELECT deleted.parent_id FROM memo_group AS deleted WHERE deleted.id = p_memo_group_id)
+     WHERE memo_group.parent_id = p_memo_group_id;
+    -- older revisions keep pointing to the group that replaced it
+    UPDATE memo_history SET group_id = p_reassign_to WHERE memo_history.group_id = p_memo_group_id;
+    DELETE FROM memo_acl WHERE memo_acl.memo_group_id = p_memo_group_id;
+    DELETE FROM memo_group WHERE memo_group.id = p_memo_group_id;
+  END; $$
+LANGUAGE 'plpgsql';
@@ -0,0 +1,98 @@
+DROP FUNCTION memo_group_acl_entries;
+DROP FUNCTION memo_acl_inherited;
+DROP FUNCTION memo_group_acl_source;
+DROP FUNCTION memo_group_move;
+/*
+
+Memo groups form a tree through memo_group.parent_id, a parent always
+belongs to the owner of its children.
+The memo_acl entries of a group apply to its subgroups too. Inheritance is per
+user group: a subgroup with its own entry for a user group overrides the one of
+its ancestors for that user group only, the others are still inherited.
+memo_group_acl_entries resolves it going up from a group, memo_acl_inherited
+going down from the entries of some user groups, both have to give the same answer.
+
+*/
+
+-- per user group, the entry of the closest ancestor (or the group itself) naming it
+CREATE OR REPLACE FUNCTION memo_group_acl_entries(p_memo_group_id memo_group.id%TYPE)
+  RETURNS TABLE (
+    o_user_group_id memo_acl.user_group_id%TYPE,
+    o_access        memo_acl.access%TYPE,
+    o_source_id     memo_group.id%TYPE
+  )
+  AS $$
+    WITH RECURSIVE ancestors (id, parent_id, depth) AS (
+      SELECT memo_group.id, memo_group.parent_id, 0
+        FROM memo_group
+       WHERE memo_group.id = p_memo_group_id
+      UNION ALL
+      SELECT memo_group.id, memo_group.parent_id, ancestors.depth + 1
+        FROM memo_group
+        JOIN ancestors ON memo_group.id = ancestors.parent_id
+       WHERE ancestors.depth < 100 -- memo_group_move keeps out cycles, just in case
+    )
+    SELECT DISTINCT ON (memo_acl.user_group_id) memo_acl.user_group_id, memo_acl.access, ancestors.id
+      FROM ancestors
+      JOIN memo_acl ON memo_acl.memo_group_id = ancestors.id
+     ORDER BY memo_acl.user_group_id, ancestors.depth;
+  $$
+LANGUAGE 'sql' STABLE;
+
+-- memo groups the user groups have access to, their memo_acl entries and the subgroups inheriting them
+CREATE OR REPLACE FUNCTION memo_acl_inherited(p_user_group_ids int4[])
+  RETURNS TABLE (
+    o_memo_group_id memo_group.id%TYPE,
+    o_user_group_id memo_acl.user_group_id%TYPE,
+    o_access        memo_acl.access%TYPE
+  )
+  AS $$
+    WITH RECURSIVE shared (memo_group_id, user_group_id, access) AS (
+      SELECT memo_acl.memo_group_id, memo_acl.user_group_id, memo_acl.access
+        FROM memo_acl
+       WHERE memo_acl.user_group_id = ANY (p_user_group_ids)
+      UNION
+      SELECT memo_group.id, shared.user_group_id, shared.access
+        FROM memo_group
+        JOIN shared ON memo_group.parent_id = shared.memo_group_id
+       -- stops where a subgroup has its own entry for the user group, that one was a starting row
+       WHERE NOT EXISTS (
+         SELECT 1
+           FROM memo_acl
+          WHERE memo_acl.memo_group_id = memo_group.id
+            AND memo_acl.user_group_id = shared.user_group_id)
+    )
+    SELECT shared.memo_group_id, shared.user_group_id, shared.access FROM shared;
+  $$
+LANGUAGE 'sql' STABLE;
+
+-- NULL parent makes it a top level group
+CREATE OR REPLACE FUNCTION memo_group_move(
+  p_memo_group_id memo_group.id%TYPE,
+  p_username      users.username%TYPE,
+  p_parent_id     memo_group.id%TYPE)
+  RETURNS void
+  AS $$
+  BEGIN
+    PERFORM memo_group_owner(p_memo_group_id, p_username);
+
+    IF p_parent_id IS NOT NULL THEN
+      PERFORM memo_group_owner(p_parent_id, p_username);
+
+      IF EXISTS (
+        WITH RECURSIVE descendants (id) AS (
+          SELECT p_memo_group_id
+          UNION
+          SELECT memo_group.id
+            FROM memo_group
+            JOIN descendants ON memo_group.parent_id = descendants.id
+        )
+        SELECT 1 FROM descendants WHERE descendants.id = p_parent_id) THEN
+        RAISE EXCEPTION 'memo group % can not go under itself or its subgroup %', p_memo_group_id, p_parent_id
+          USING ERRCODE = '22023'; -- invalid_parameter_value
+      END IF;
+    END IF;
+
+    UPDATE memo_group SET parent_id = p_parent_id WHERE memo_group.id = p_memo_group_id;
+  END; $$
+LANGUAGE 'plpgsql';
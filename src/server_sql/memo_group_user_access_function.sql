@@ -8,15 +8,8 @@ CREATE OR REPLACE FUNCTION memo_group_user_access (
 DECLARE
   v_access memo_acl.access%TYPE;
 BEGIN
-  SELECT MAX(memo_acl.access) INTO v_access
-    FROM user_group,
-         user_group_detail,
-         memo_acl
-   WHERE user_group.id = user_group_detail.user_group_id
-     AND user_group.id = memo_acl.user_group_id
-     AND user_group_detail.user_id = p_user_id -- requesting user is in this group
-     -- and user_group.user_id <> o_requester_id -- not owner of the group
-     AND memo_acl.memo_group_id = p_memo_group_id;
+  -- on the memo group or inherited from a parent group
+  v_access := NULLIF(memo_group_acl(p_memo_group_id, p_user_id), 0);
 
   IF v_access IS NULL OR (p_min_required IS NOT NULL AND v_access < p_min_required) THEN
     RAISE EXCEPTION 'User % does not have permissions on memo group %', p_user_id, p_memo_group_id
@@ -0,0 +1,74 @@
+DROP FUNCTION memo_history_list;
+DROP FUNCTION memo_history_read;
+/*
+
+Revisions of a memo as saved by memo_write into memo_history.
+Access follows memo_read: the owner of the memo or anybody with an
+acl entry on the memo group can see the history.
+
+*/
+
+CREATE OR REPLACE FUNCTION memo_history_list(p_memo_id memo.id%TYPE, p_username users.username%TYPE)
+  RETURNS TABLE (
+    o_id            memo_history.id%TYPE,
+    o_memo_id       memo_history.memo_id%TYPE,
+    o_title         memo_history.title%TYPE,
+    o_savetime      memo_history.savetime%TYPE,
+    o_saveuser_id   users.id%TYPE,
+    o_saveuser_name users.username%TYPE
+  )
+  AS $$
+  BEGIN
+    -- raises the same exceptions as reading the memo itself
+    PERFORM memo_read(p_memo_id, p_username);
+
+    RETURN QUERY
+      SELECT memo_history.id, memo_history.memo_id, memo_history.title, memo_history.savetime, users.id, users.username
+        FROM memo_history
+        JOIN users ON memo_history.saveuser_id = users.id
+       WHERE memo_history.memo_id = p_memo_id
+       ORDER BY memo_history.savetime DESC, memo_history.id DESC;
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION memo_history_read(p_memo_id memo.id%TYPE, p_history_id memo_history.id%TYPE, p_username users.username%TYPE,
+
+    OUT o_id              memo_history.id%TYPE,
+    OUT o_memo_id         memo_history.memo_id%TYPE,
+    OUT o_title           memo_history.title%TYPE,
+    OUT o_memotext        memo_history.memotext%TYPE,
+    OUT o_savetime        memo_history.savetime%TYPE,
+    OUT o_memo_group_id   memo_history.group_id%TYPE,
+    OUT o_memo_group_name memo_group.name%TYPE,
+    OUT o_saveuser_id     users.id%TYPE,
+    OUT o_saveuser_name   users.username%TYPE
+  )
+  AS $$
+  BEGIN
+    -- raises the same exceptions as reading the memo itself
+    PERFORM memo_read(p_memo_id, p_username);
+
+    SELECT
+     memo_history.id,
+     memo_history.memo_id,
+     memo_history.title,
+     memo_history.memotext,
+     memo_history.savetime,
+     memo_history.group_id,
+     memo_group.name,
+     users.id,
+     users.username
+     INTO o_id, o_memo_id, o_title, o_memotext, o_savetime, o_memo_group_id, o_memo_group_name, o_saveuser_id, o_saveuser_name
+
+     FROM memo_history
+     JOIN users ON memo_history.saveuser_id = users.id
+     LEFT JOIN memo_group ON memo_history.group_id = memo_group.id
+     WHERE memo_history.id = p_history_id
+       AND memo_history.memo_id = p_memo_id
+    ;
+
+    IF o_id IS NULL THEN
+      RAISE EXCEPTION 'No revision % for memo %', p_history_id, p_memo_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
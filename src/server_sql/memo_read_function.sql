@@ -44,6 +44,7 @@ CREATE OR REPLACE FUNCTION memo_read(p_memo_id integer, p_username users.usernam
      JOIN users ON memo.user_id = users.id
      LEFT JOIN memo_group ON memo.group_id = memo_group.id
      WHERE memo.id = p_memo_id
+       AND memo.deleted_on IS NULL -- memos in the trash are only listed by the trash
     ;
 
     -- check if we found the memo
@@ -54,15 +55,14 @@ CREATE OR REPLACE FUNCTION memo_read(p_memo_id integer, p_username users.usernam
     -- check if the requester is allowed to see the memo. if he's the owner he can by default
     IF o_user_id <> o_requester_id THEN
     --  if requester is not the memo owner see if there's an acl entry to grant permissions
-      SELECT MAX(memo_acl.access) INTO v_access
-        FROM user_group,
-             user_group_detail,
-             memo_acl
-       WHERE user_group.id = user_group_detail.user_group_id
-         AND user_group.id = memo_acl.user_group_id
-         AND user_group_detail.user_id = o_requester_id -- requesting user is in this group
-         -- and user_group.user_id <> o_requester_id -- not owner of the group
-         AND memo_acl.memo_group_id = o_memo_group_id;
+      -- on the memo group or inherited from a parent group
+      v_access := NULLIF(memo_group_acl(o_memo_group_id, o_requester_id), 0);
+
+      -- or the memo alone shared with him
+      SELECT GREATEST(v_access, MAX(memo_share.access)) INTO v_access
+        FROM memo_share
+       WHERE memo_share.memo_id = o_id
+         AND memo_share.user_id = o_requester_id;
 
          IF v_access IS NULL THEN
            RAISE EXCEPTION 'User % does not have permissions on memo %', p_username, p_memo_id
@@ -0,0 +1,89 @@
+DROP FUNCTION memo_restore;
+/*
+
+Restores a revision from memo_history as the current memo.
+The actual save goes through memo_write, so the same rules apply and the
+overwritten version ends up in memo_history, which makes the restore undoable:
+1) Owner of the memo restores title, memotext and group
+2) Another user, if allowed to write, only restores the memotext
+
+Output parameters are named like the ones of memo_write to return the same row.
+
+*/
+
+CREATE OR REPLACE FUNCTION memo_restore(
+    INOUT io_memo_id        memo.id%TYPE,
+    IN    i_history_id      memo_history.id%TYPE,
+      OUT io_memo_title     memo.title%TYPE,
+      OUT io_memo_memotext  memo.memotext%TYPE,
+    INOUT io_savetime       memo.savetime%TYPE,
+      OUT io_memo_group_id  memo.group_id%TYPE,
+      OUT o_memo_group_name memo_group.name%TYPE,
+      OUT o_user_id         memo.user_id%TYPE,
+      OUT o_username        users.username%TYPE,
+      OUT o_requester_id    users.id%TYPE,
+    INOUT io_requester_name users.username%TYPE
+  )
+  AS $$
+  DECLARE
+    v_title         memo.title%TYPE;
+    v_memotext      memo.memotext%TYPE;
+    v_memo_group_id memo.group_id%TYPE;
+    v_owner_id      memo.user_id%TYPE;
+    v_old_title     memo_history.title%TYPE;
+    v_old_memotext  memo_history.memotext%TYPE;
+    v_old_group_id  memo_history.group_id%TYPE;
+  BEGIN
+    BEGIN
+    -- check the requester user exists
+    SELECT users.id INTO STRICT o_requester_id FROM users WHERE users.username = io_requester_name;
+    EXCEPTION 
+      WHEN NO_DATA_FOUND THEN
+        RAISE EXCEPTION 'user % not found', io_requester_name USING ERRCODE = '28000'; -- invalid_authorization_specification
+      WHEN TOO_MANY_ROWS THEN
+        RAISE EXCEPTION 'fetched more than one user for %', io_requester_name USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END;
+
+    BEGIN
+      SELECT memo.title, memo.memotext, memo.group_id, memo.user_id
+        INTO STRICT v_title, v_memotext, v_memo_group_id, v_owner_id
+        FROM memo
+       WHERE memo.id = io_memo_id
+         AND memo.deleted_on IS NULL;
+    EXCEPTION
+      WHEN NO_DATA_FOUND THEN
+        RAISE EXCEPTION 'memo % not found', io_memo_id USING ERRCODE = '02000'; -- no_data
+    END;
+
+    BEGIN
+      SELECT memo_history.title, memo_history.memotext, memo_history.group_id
+        INTO STRICT v_old_title, v_old_memotext, v_old_group_id
+        FROM memo_history
+       WHERE memo_history.id = i_history_id
+         AND memo_history.memo_id = io_memo_id;
+    EXCEPTION
+      WHEN NO_DATA_FOUND THEN
+        RAISE EXCEPTION 'No revision % for memo %', i_history_id, io_memo_id USING ERRCODE = '02000'; -- no_data
+    END;
+
+    -- an empty memo would be moved to the trash by memo_write
+    IF LENGTH(COALESCE(v_old_title, '')) + LENGTH(COALESCE(v_old_memotext, '')) = 0 THEN
+      RAISE EXCEPTION 'Revision % of memo % is empty', i_history_id, io_memo_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    v_memotext := v_old_memotext;
+    IF o_requester_id = v_owner_id THEN
+      -- 1) owner restores everything
+      v_title := v_old_title;
+      v_memo_group_id := v_old_group_id;
+    END IF;
+    -- 2) otherwise title and group stay as they are now
+
+    SELECT w.io_memo_id, w.io_memo_title, w.io_memo_memotext, w.io_savetime, w.io_memo_group_id,
+           w.o_memo_group_name, w.o_user_id, w.o_username, w.o_requester_id, w.io_requester_name
+      INTO io_memo_id, io_memo_title, io_memo_memotext, io_savetime, io_memo_group_id,
+           o_memo_group_name, o_user_id, o_username, o_requester_id, io_requester_name
+      -- restoring is an explicit choice, no check against the version the client saw
+      FROM memo_write(io_memo_id, v_title, v_memotext, io_savetime, NULL, v_memo_group_id, io_requester_name) AS w;
+  END; $$
+LANGUAGE 'plpgsql';
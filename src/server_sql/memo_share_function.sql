@@ -0,0 +1,93 @@
+DROP FUNCTION memo_share_access;
+DROP FUNCTION memo_share_set;
+DROP FUNCTION memo_share_revoke;
+/*
+
+A single memo shared with a single user through memo_share.
+Levels are the ones of memo_acl: 1 read, 2 write.
+Only the owner of the memo can change them.
+
+*/
+
+-- level given to the user on the memo itself, 0 without any
+CREATE OR REPLACE FUNCTION memo_share_access(p_memo_id memo.id%TYPE, p_user_id users.id%TYPE)
+  RETURNS int
+  AS $$
+    SELECT COALESCE(MAX(memo_share.access), 0)
+      FROM memo_share
+     WHERE memo_share.memo_id = p_memo_id
+       AND memo_share.user_id = p_user_id;
+  $$
+LANGUAGE 'sql' STABLE;
+
+CREATE OR REPLACE FUNCTION memo_share_set(
+  p_memo_id    memo.id%TYPE,
+  p_username   users.username%TYPE,
+  p_share_with users.username%TYPE,
+  p_access     memo_share.access%TYPE)
+  RETURNS void
+  AS $$
+  DECLARE
+    v_owner_id users.id%TYPE;
+    v_user_id  users.id%TYPE;
+  BEGIN
+    PERFORM audit_actor(p_username);
+    SELECT users.id INTO v_owner_id
+      FROM memo
+      JOIN users ON memo.user_id = users.id
+     WHERE memo.id = p_memo_id
+       AND memo.deleted_on IS NULL
+       AND users.username = p_username;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'user % does not own memo %', p_username, p_memo_id
+        USING ERRCODE = '2F004'; -- reading_sql_data_not_permitted
+    END IF;
+
+    IF p_access NOT IN (access_read(), access_write()) THEN
+      RAISE EXCEPTION 'access % is neither read (1) nor write (2)', p_access USING ERRCODE = '22023'; -- invalid_parameter_value
+    END IF;
+    SELECT users.id INTO v_user_id FROM users WHERE users.username = p_share_with;
+    IF v_user_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_share_with USING ERRCODE = '02000'; -- no_data
+    END IF;
+    IF v_user_id = v_owner_id THEN
+      RAISE EXCEPTION 'memo % already belongs to %', p_memo_id, p_share_with USING ERRCODE = '22023'; -- invalid_parameter_value
+    END IF;
+
+    UPDATE memo_share SET access = p_access
+     WHERE memo_share.memo_id = p_memo_id
+       AND memo_share.user_id = v_user_id;
+    IF NOT FOUND THEN
+      INSERT INTO memo_share (memo_id, user_id, access) VALUES (p_memo_id, v_user_id, p_access);
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION memo_share_revoke(
+  p_memo_id    memo.id%TYPE,
+  p_username   users.username%TYPE,
+  p_share_with users.username%TYPE)
+  RETURNS void
+  AS $$
+  BEGIN
+    PERFORM audit_actor(p_username);
+    IF NOT EXISTS (
+      SELECT 1
+        FROM memo
+        JOIN users ON memo.user_id = users.id
+       WHERE memo.id = p_memo_id
+         AND users.username = p_username) THEN
+      RAISE EXCEPTION 'user % does not own memo %', p_username, p_memo_id
+        USING ERRCODE = '2F004'; -- reading_sql_data_not_permitted
+    END IF;
+
+    DELETE FROM memo_share
+     USING users
+     WHERE memo_share.memo_id = p_memo_id
+       AND memo_share.user_id = users.id
+       AND users.username = p_share_with;
+    IF NOT FOUND THEN
+      RAISE EXCEPTION 'memo % is not shared with %', p_memo_id, p_share_with USING ERRCODE = '02000'; -- no_data
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
@@ -0,0 +1,54 @@
+DROP FUNCTION memo_visible;
+DROP FUNCTION memo_group_shared;
+/*
+
+Memos a user can see: his own, the ones in memo groups shared with him
+through memo_acl and the ones shared with him alone through memo_share.
+Memos in the trash are left out.
+Files in filestore follow the same rule with their memo group.
+
+*/
+
+-- memo groups other users shared with this one through memo_acl, directly or on a parent group.
+-- Goes down from the entries of his user groups instead of checking every memo group
+CREATE OR REPLACE FUNCTION memo_group_shared(p_username users.username%TYPE)
+  RETURNS SETOF memo_group.id%TYPE
+  AS $$
+    SELECT DISTINCT memo_group.id
+      FROM users
+      JOIN memo_acl_inherited(ARRAY(
+             SELECT user_group_detail.user_group_id
+               FROM user_group_detail
+              WHERE user_group_detail.user_id = users.id)) AS shared ON shared.o_access > 0
+      JOIN memo_group ON memo_group.id = shared.o_memo_group_id
+     WHERE users.username = p_username
+       AND memo_group.user_id <> users.id;
+  $$
+LANGUAGE 'sql' STABLE;
+
+CREATE OR REPLACE FUNCTION memo_visible(p_username users.username%TYPE)
+  RETURNS TABLE (
+    o_memo_id memo.id%TYPE,
+    o_shared  boolean
+  )
+  AS $$
+    SELECT memo.id, false
+      FROM memo
+      JOIN users ON memo.user_id = users.id
+     WHERE users.username = p_username
+       AND memo.deleted_on IS NULL
+
+    UNION ALL
+
+    SELECT memo.id, true
+      FROM memo
+     WHERE memo.deleted_on IS NULL
+       AND memo.user_id <> (SELECT users.id FROM users WHERE users.username = p_username)
+       AND (memo.group_id IN (SELECT memo_group_shared(p_username))
+         OR memo.id IN (
+           SELECT memo_share.memo_id
+             FROM memo_share
+             JOIN users ON memo_share.user_id = users.id
+            WHERE users.username = p_username));
+  $$
+LANGUAGE 'sql' STABLE;
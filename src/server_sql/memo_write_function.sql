@@ -7,9 +7,14 @@ Permissions:
 3) Memo if specified, has to exist
 4) If title, memotext and group_id are the same, don't do the update
 5) Owner can only change group_id to another one he owns
+6) If the savetime the client based the edit on is given, it has to match the saved memo
 Owner of memo can change title, memotext. 
+Owner saving an empty memo moves it to the trash, memos in the trash can't be written.
 
-Another user can if allowed, only change memotext
+Another user can if allowed, only change memotext. Write access comes from memo_acl
+on the memo group or from memo_share on the memo.
+The server checks the same rules beforehand with permissions::Action, these checks
+stay as the last line for anything calling memo_write directly.
 
 */
 
@@ -19,6 +24,7 @@ CREATE OR REPLACE FUNCTION memo_write(
     INOUT io_memo_title     memo.title%TYPE, 
     INOUT io_memo_memotext  memo.memotext%TYPE, 
     INOUT io_savetime       memo.savetime%TYPE,
+    IN    i_base_savetime   memo.savetime%TYPE,
     INOUT io_memo_group_id  memo.group_id%TYPE,
       OUT o_memo_group_name memo_group.name%TYPE,
       OUT o_user_id         memo.user_id%TYPE,
@@ -82,7 +88,10 @@ CREATE OR REPLACE FUNCTION memo_write(
 	  INTO STRICT v_old_title, v_old_memotext, v_old_memo_group_id, o_user_id, v_old_saveuser_id, v_old_savetime, o_username
 	  FROM memo
 	  JOIN users ON memo.user_id = users.id 
-         WHERE memo.id = io_memo_id;
+         WHERE memo.id = io_memo_id
+           AND memo.deleted_on IS NULL
+           -- lock the row so a concurrent save waits and then sees the new savetime
+           FOR UPDATE OF memo;
       EXCEPTION
       WHEN NO_DATA_FOUND THEN
         -- 3) Memo if specified, has to exist
@@ -97,6 +106,12 @@ CREATE OR REPLACE FUNCTION memo_write(
 	  RAISE NOTICE 'Memo values for % did not change, not saving', io_memo_id;
         RETURN;
       END IF;
+      IF i_base_savetime IS NOT NULL AND i_base_savetime IS DISTINCT FROM v_old_savetime THEN
+        -- 6) somebody saved the memo after the version the client edited
+        RAISE EXCEPTION 'memo % was saved at %, the edit is based on the version from %',
+          io_memo_id, v_old_savetime, i_base_savetime
+          USING ERRCODE = '40001'; -- serialization_failure
+      END IF;
       IF io_memo_group_id IS NOT NULL AND v_memo_group_user_id <> o_user_id THEN
         -- 5) Owner can only change group_id to another one he owns
         RAISE EXCEPTION 'New memogroup belongs to user %, not user % who owns memo %',
@@ -107,8 +122,8 @@ CREATE OR REPLACE FUNCTION memo_write(
       IF o_requester_id = o_user_id THEN
         -- owner of the memo has full rights on the memo
         IF v_empty_content THEN
-          -- delete the memo
-          DELETE FROM memo WHERE id = io_memo_id;
+          -- move the memo to the trash, purged later
+          UPDATE memo SET deleted_on = io_savetime WHERE id = io_memo_id;
           io_memo_id := NULL;
         ELSE
           -- modify the memo
@@ -134,8 +149,11 @@ CREATE OR REPLACE FUNCTION memo_write(
             o_requester_id, io_requester_name, io_memo_id, o_user_id
             USING ERRCODE = '2F002'; -- modifying_sql_data_not_permitted
         END IF;
-	-- check permission for user
-	PERFORM memo_group_user_access(v_old_memo_group_id, o_requester_id, 2);
+	-- check permission for user, through the memo group or on the memo alone
+	IF GREATEST(memo_group_acl(v_old_memo_group_id, o_requester_id), memo_share_access(io_memo_id, o_requester_id)) < access_write() THEN
+	  RAISE EXCEPTION 'User % does not have permissions on memo %', o_requester_id, io_memo_id
+	    USING ERRCODE = '2F003'; -- prohibited_sql_statement_attempted
+	END IF;
 
         -- update the memo
           INSERT INTO memo_history (memo_id, group_id, title, memotext, user_id, saveuser_id, savetime)
@@ -0,0 +1,116 @@
+DROP FUNCTION memo_transfer;
+DROP FUNCTION memo_group_transfer;
+DROP FUNCTION transfer_users;
+/*
+
+Gives a memo, or a memo group with its memos and files, to another user.
+Only the owner or the admin (user 1) can do it, every transfer is recorded in
+ownership_transfer. memo_history keeps the revisions as they were saved, memo_acl
+entries stay with the memo group so everybody it was shared with keeps access.
+
+*/
+
+-- requester and new owner, raises when either is missing or the requester may not transfer
+CREATE OR REPLACE FUNCTION transfer_users(
+  p_owner_id  users.id%TYPE,
+  p_username  users.username%TYPE,
+  p_new_owner users.username%TYPE,
+  OUT o_requester_id users.id%TYPE,
+  OUT o_new_owner_id users.id%TYPE)
+  AS $$
+  BEGIN
+    SELECT users.id INTO o_requester_id FROM users WHERE users.username = p_username;
+    IF o_requester_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_username USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END IF;
+    IF o_requester_id <> p_owner_id AND o_requester_id <> 1 THEN
+      RAISE EXCEPTION 'user % is neither the owner nor the admin', p_username
+        USING ERRCODE = '2F004'; -- reading_sql_data_not_permitted
+    END IF;
+
+    SELECT users.id INTO o_new_owner_id FROM users WHERE users.username = p_new_owner;
+    IF o_new_owner_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_new_owner USING ERRCODE = '02000'; -- no_data
+    END IF;
+    IF o_new_owner_id = p_owner_id THEN
+      RAISE EXCEPTION 'user % is already the owner', p_new_owner USING ERRCODE = '22023'; -- invalid_parameter_value
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION memo_transfer(
+  p_memo_id   memo.id%TYPE,
+  p_username  users.username%TYPE,
+  p_new_owner users.username%TYPE,
+  p_savetime  memo.savetime%TYPE)
+  RETURNS void
+  AS $$
+  DECLARE
+    v_owner_id     users.id%TYPE;
+    v_requester_id users.id%TYPE;
+    v_new_owner_id users.id%TYPE;
+  BEGIN
+    PERFORM audit_actor(p_username);
+    SELECT memo.user_id INTO v_owner_id
+      FROM memo
+     WHERE memo.id = p_memo_id
+       AND memo.deleted_on IS NULL
+       FOR UPDATE;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'No memo with id %', p_memo_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    SELECT o_requester_id, o_new_owner_id INTO v_requester_id, v_new_owner_id
+      FROM transfer_users(v_owner_id, p_username, p_new_owner);
+
+    -- the memo group stays with its owner, memos only go in groups of their owner
+    UPDATE memo SET user_id = v_new_owner_id, group_id = NULL WHERE memo.id = p_memo_id;
+    -- the new owner doesn't need it shared any more
+    DELETE FROM memo_share WHERE memo_share.memo_id = p_memo_id AND memo_share.user_id = v_new_owner_id;
+
+    INSERT INTO ownership_transfer (object_type, object_id, from_user_id, to_user_id, transferred_by, transferred_on)
+    VALUES ('memo', p_memo_id, v_owner_id, v_new_owner_id, v_requester_id, p_savetime);
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION memo_group_transfer(
+  p_memo_group_id memo_group.id%TYPE,
+  p_username      users.username%TYPE,
+  p_new_owner     users.username%TYPE,
+  p_savetime      memo.savetime%TYPE)
+  RETURNS void
+  AS $$
+  DECLARE
+    v_owner_id     users.id%TYPE;
+    v_requester_id users.id%TYPE;
+    v_new_owner_id users.id%TYPE;
+  BEGIN
+    PERFORM audit_actor(p_username);
+    SELECT memo_group.user_id INTO v_owner_id
+      FROM memo_group
+     WHERE memo_group.id = p_memo_group_id
+       FOR UPDATE;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'memo group % not found', p_memo_group_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    SELECT o_requester_id, o_new_owner_id INTO v_requester_id, v_new_owner_id
+      FROM transfer_users(v_owner_id, p_username, p_new_owner);
+
+    -- parents and subgroups stay with the previous owner, the subgroups take the place of the group
+    UPDATE memo_group SET parent_id = (SELECT moved.parent_id FROM memo_group AS moved WHERE moved.id = p_memo_group_id)
+     WHERE memo_group.parent_id = p_memo_group_id;
+    UPDATE memo_group SET user_id = v_new_owner_id, parent_id = NULL WHERE memo_group.id = p_memo_group_id;
+    -- memos in the trash go along, they would be restored into the group
+    UPDATE memo SET user_id = v_new_owner_id WHERE memo.group_id = p_memo_group_id AND memo.user_id = v_owner_id;
+    UPDATE filestore SET user_id = v_new_owner_id WHERE filestore.memo_group_id = p_memo_group_id AND filestore.user_id = v_owner_id;
+    DELETE FROM memo_share
+     USING memo
+     WHERE memo_share.memo_id = memo.id
+       AND memo.group_id = p_memo_group_id
+       AND memo_share.user_id = v_new_owner_id;
+
+    INSERT INTO ownership_transfer (object_type, object_id, from_user_id, to_user_id, transferred_by, transferred_on)
+    VALUES ('memo_group', p_memo_group_id, v_owner_id, v_new_owner_id, v_requester_id, p_savetime);
+  END; $$
+LANGUAGE 'plpgsql';
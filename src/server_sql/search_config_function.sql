@@ -0,0 +1,61 @@
+DROP TRIGGER memo_tsv_trigger ON memo;
+DROP FUNCTION memo_tsv_update;
+DROP FUNCTION user_search_config_set;
+DROP FUNCTION memo_search_config;
+/*
+
+Text search configuration (language) of the memos.
+Every user can choose one in users.search_config, otherwise the default of the
+deployment applies, set it for the database with:
+  ALTER DATABASE organizator SET default_text_search_config = 'pg_catalog.romanian';
+memo.tsv is indexed with the configuration of the owner, kept in memo.search_config,
+and the search builds the query with the same configuration for every memo.
+
+*/
+
+CREATE OR REPLACE FUNCTION memo_search_config(p_user_id users.id%TYPE)
+  RETURNS regconfig
+  AS $$
+    SELECT COALESCE(users.search_config, current_setting('default_text_search_config')::regconfig)
+      FROM users
+     WHERE users.id = p_user_id;
+  $$
+LANGUAGE 'sql' STABLE;
+
+CREATE OR REPLACE FUNCTION memo_tsv_update()
+  RETURNS trigger
+  AS $$
+  BEGIN
+    NEW.search_config := memo_search_config(NEW.user_id);
+    NEW.tsv := to_tsvector(NEW.search_config, unaccent(COALESCE(NEW.title, '') || COALESCE(NEW.memotext, '')));
+    RETURN NEW;
+  END; $$
+LANGUAGE 'plpgsql';
+
+-- any update of search_config reindexes the memo with the current configuration of the owner
+CREATE TRIGGER memo_tsv_trigger
+  BEFORE INSERT OR UPDATE OF title, memotext, user_id, search_config ON memo
+  FOR EACH ROW EXECUTE PROCEDURE memo_tsv_update();
+
+-- index the memos saved before the trigger existed, a no-op once every memo has its tsv
+UPDATE memo SET search_config = NULL WHERE memo.tsv IS NULL;
+
+-- NULL goes back to the deployment default
+CREATE OR REPLACE FUNCTION user_search_config_set(p_username users.username%TYPE, p_search_config text)
+  RETURNS void
+  AS $$
+  DECLARE
+    v_user_id users.id%TYPE;
+  BEGIN
+    UPDATE users SET search_config = p_search_config::regconfig
+     WHERE users.username = p_username
+    RETURNING users.id INTO v_user_id;
+
+    IF v_user_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_username USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END IF;
+
+    -- reindex the memos of the user
+    UPDATE memo SET search_config = NULL WHERE memo.user_id = v_user_id;
+  END; $$
+LANGUAGE 'plpgsql';
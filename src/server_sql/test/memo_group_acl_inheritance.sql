@@ -0,0 +1,45 @@
+/*
+
+Run with psql against a database with the functions loaded, everything is rolled back.
+A parent group shared with user group A, its subgroup shared with user group B:
+A keeps the access inherited on the subgroup.
+
+*/
+BEGIN;
+
+DO $$
+  DECLARE
+    v_owner_id  users.id%TYPE := 1;
+    v_parent_id memo_group.id%TYPE;
+    v_child_id  memo_group.id%TYPE;
+    v_team_a    user_group.id%TYPE;
+    v_team_b    user_group.id%TYPE;
+  BEGIN
+    INSERT INTO memo_group (name, user_id) VALUES ('acl test parent', v_owner_id) RETURNING id INTO v_parent_id;
+    INSERT INTO memo_group (name, user_id, parent_id) VALUES ('acl test child', v_owner_id, v_parent_id) RETURNING id INTO v_child_id;
+    INSERT INTO user_group (user_group_name, user_id) VALUES ('acl test a', v_owner_id) RETURNING id INTO v_team_a;
+    INSERT INTO user_group (user_group_name, user_id) VALUES ('acl test b', v_owner_id) RETURNING id INTO v_team_b;
+
+    INSERT INTO memo_acl (memo_group_id, user_group_id, access) VALUES (v_parent_id, v_team_a, 2);
+    INSERT INTO memo_acl (memo_group_id, user_group_id, access) VALUES (v_child_id, v_team_b, 1);
+
+    ASSERT (SELECT o_access FROM memo_group_acl_entries(v_child_id) WHERE o_user_group_id = v_team_a) = 2,
+      'team a lost the access inherited from the parent';
+    ASSERT (SELECT o_source_id FROM memo_group_acl_entries(v_child_id) WHERE o_user_group_id = v_team_a) = v_parent_id,
+      'team a should get its access from the parent';
+    ASSERT (SELECT o_access FROM memo_group_acl_entries(v_child_id) WHERE o_user_group_id = v_team_b) = 1,
+      'team b should get the access of the child';
+    ASSERT NOT EXISTS (SELECT 1 FROM memo_group_acl_entries(v_parent_id) WHERE o_user_group_id = v_team_b),
+      'team b has no access to the parent';
+    ASSERT EXISTS (SELECT 1 FROM memo_acl_inherited(ARRAY[v_team_a]) WHERE o_memo_group_id = v_child_id AND o_access = 2),
+      'going down, team a should reach the child';
+
+    -- an entry of its own for team a on the child overrides the inherited one
+    INSERT INTO memo_acl (memo_group_id, user_group_id, access) VALUES (v_child_id, v_team_a, 1);
+    ASSERT (SELECT o_access FROM memo_group_acl_entries(v_child_id) WHERE o_user_group_id = v_team_a) = 1,
+      'the entry on the child should override the parent for team a';
+    ASSERT (SELECT array_agg(o_access) FROM memo_acl_inherited(ARRAY[v_team_a]) WHERE o_memo_group_id = v_child_id) = ARRAY[1],
+      'going down, the entry on the child should stop the inherited one';
+  END; $$;
+
+ROLLBACK;
@@ -0,0 +1,150 @@
+DROP FUNCTION user_group_invite;
+DROP FUNCTION user_group_invitation_answer;
+DROP FUNCTION user_group_member_remove;
+DROP FUNCTION user_group_delete;
+DROP FUNCTION user_group_owner;
+/*
+
+Membership of the user groups memo groups are shared with.
+Only the owner of a user group (user_group.user_id) can change it: he invites
+users and they become members, with the access of the group, once they accept.
+
+*/
+
+-- id of the owner, raises when the group doesn't exist or belongs to somebody else
+CREATE OR REPLACE FUNCTION user_group_owner(p_user_group_id user_group.id%TYPE, p_username users.username%TYPE)
+  RETURNS users.id%TYPE
+  AS $$
+  DECLARE
+    v_owner_id     users.id%TYPE;
+    v_requester_id users.id%TYPE;
+  BEGIN
+    SELECT users.id INTO v_requester_id FROM users WHERE users.username = p_username;
+    IF v_requester_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_username USING ERRCODE = '28000'; -- invalid_authorization_specification
+    END IF;
+
+    SELECT user_group.user_id INTO v_owner_id FROM user_group WHERE user_group.id = p_user_group_id FOR UPDATE;
+    IF v_owner_id IS NULL THEN
+      RAISE EXCEPTION 'user group % not found', p_user_group_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+    IF v_owner_id <> v_requester_id THEN
+      RAISE EXCEPTION 'user % does not own user group %', p_username, p_user_group_id
+        USING ERRCODE = '2F004'; -- reading_sql_data_not_permitted
+    END IF;
+
+    RETURN v_owner_id;
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION user_group_invite(
+  p_user_group_id user_group.id%TYPE,
+  p_username      users.username%TYPE,
+  p_member        users.username%TYPE,
+  p_savetime      user_group_invitation.invited_on%TYPE)
+  RETURNS void
+  AS $$
+  DECLARE
+    v_owner_id  users.id%TYPE;
+    v_member_id users.id%TYPE;
+  BEGIN
+    v_owner_id := user_group_owner(p_user_group_id, p_username);
+
+    SELECT users.id INTO v_member_id FROM users WHERE users.username = p_member;
+    IF v_member_id IS NULL THEN
+      RAISE EXCEPTION 'user % not found', p_member USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    -- nothing to do for members and users already invited
+    IF EXISTS (
+      SELECT 1 FROM user_group_detail
+       WHERE user_group_detail.user_group_id = p_user_group_id
+         AND user_group_detail.user_id = v_member_id) THEN
+      RETURN;
+    END IF;
+
+    INSERT INTO user_group_invitation (user_group_id, user_id, invited_by, invited_on)
+    SELECT p_user_group_id, v_member_id, v_owner_id, p_savetime
+     WHERE NOT EXISTS (
+       SELECT 1 FROM user_group_invitation
+        WHERE user_group_invitation.user_group_id = p_user_group_id
+          AND user_group_invitation.user_id = v_member_id
+          AND user_group_invitation.status = 'pending');
+  END; $$
+LANGUAGE 'plpgsql';
+
+-- only the invited user can answer, the invitation has to be pending
+CREATE OR REPLACE FUNCTION user_group_invitation_answer(
+  p_invitation_id user_group_invitation.id%TYPE,
+  p_username      users.username%TYPE,
+  p_accept        boolean,
+  p_savetime      user_group_invitation.answered_on%TYPE)
+  RETURNS void
+  AS $$
+  DECLARE
+    v_user_group_id user_group.id%TYPE;
+    v_user_id       users.id%TYPE;
+  BEGIN
+    PERFORM audit_actor(p_username);
+    SELECT user_group_invitation.user_group_id, users.id INTO v_user_group_id, v_user_id
+      FROM user_group_invitation
+      JOIN users ON user_group_invitation.user_id = users.id
+     WHERE user_group_invitation.id = p_invitation_id
+       AND user_group_invitation.status = 'pending'
+       AND users.username = p_username
+       FOR UPDATE OF user_group_invitation;
+    IF v_user_id IS NULL THEN
+      RAISE EXCEPTION 'no pending invitation % for %', p_invitation_id, p_username USING ERRCODE = '02000'; -- no_data
+    END IF;
+
+    UPDATE user_group_invitation
+       SET status = CASE WHEN p_accept THEN 'accepted' ELSE 'declined' END,
+           answered_on = p_savetime
+     WHERE user_group_invitation.id = p_invitation_id;
+
+    IF p_accept THEN
+      INSERT INTO user_group_detail (user_group_id, user_id)
+      SELECT v_user_group_id, v_user_id
+       WHERE NOT EXISTS (
+         SELECT 1 FROM user_group_detail
+          WHERE user_group_detail.user_group_id = v_user_group_id
+            AND user_group_detail.user_id = v_user_id);
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
+
+CREATE OR REPLACE FUNCTION user_group_member_remove(
+  p_user_group_id user_group.id%TYPE,
+  p_username      users.username%TYPE,
+  p_member        users.username%TYPE)
+  RETURNS void
+  AS $$
+  BEGIN
+    PERFORM audit_actor(p_username);
+    PERFORM user_group_owner(p_user_group_id, p_username);
+
+    DELETE FROM user_group_detail
+     USING users
+     WHERE user_group_detail.user_group_id = p_user_group_id
+       AND user_group_detail.user_id = users.id
+       AND users.username = p_member;
+
+    IF NOT FOUND THEN
+      RAISE EXCEPTION 'user % is not in user group %', p_member, p_user_group_id USING ERRCODE = '02000'; -- no_data
+    END IF;
+  END; $$
+LANGUAGE 'plpgsql';
+
+-- the memo groups shared with it are no longer shared
+CREATE OR REPLACE FUNCTION user_group_delete(p_user_group_id user_group.id%TYPE, p_username users.username%TYPE)
+  RETURNS void
+  AS $$
+  BEGIN
+    PERFORM audit_actor(p_username);
+    PERFORM user_group_owner(p_user_group_id, p_username);
+
+    DELETE FROM memo_acl WHERE memo_acl.user_group_id = p_user_group_id;
+    DELETE FROM user_group_detail WHERE user_group_detail.user_group_id = p_user_group_id;
+    DELETE FROM user_group WHERE user_group.id = p_user_group_id;
+  END; $$
+LANGUAGE 'plpgsql';
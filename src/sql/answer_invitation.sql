@@ -0,0 +1 @@
+SELECT user_group_invitation_answer($1, $2, $3, $4);
@@ -0,0 +1,7 @@
+UPDATE memo
+SET deleted_on = $3
+FROM users
+WHERE memo.id = $1
+  AND memo.user_id = users.id
+  AND users.username = $2
+  AND memo.deleted_on IS NULL;
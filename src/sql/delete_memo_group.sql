@@ -0,0 +1 @@
+SELECT memo_group_delete($1, $2, $3);
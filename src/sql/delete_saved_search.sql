@@ -0,0 +1,5 @@
+DELETE FROM saved_search
+USING users
+WHERE saved_search.id = $1
+  AND saved_search.user_id = users.id
+  AND users.username = $2;
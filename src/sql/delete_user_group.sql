@@ -0,0 +1 @@
+SELECT user_group_delete($1, $2);
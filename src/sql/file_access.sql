@@ -0,0 +1 @@
+SELECT file_access($1, $2) AS access;
@@ -0,0 +1,22 @@
+WITH requester AS (
+  SELECT users.id FROM users WHERE users.username = $1
+)
+SELECT audit_log.id, audit_log.event, audit_log.changed_on,
+       audit_log.actor_id, actor.username AS actor_name,
+       audit_log.memo_group_id, audit_log.user_group_id,
+       audit_log.user_id, affected.username, audit_log.owner_id,
+       audit_log.before, audit_log.after
+  FROM audit_log
+ CROSS JOIN requester
+  LEFT JOIN users AS actor ON audit_log.actor_id = actor.id
+  LEFT JOIN users AS affected ON audit_log.user_id = affected.id
+ -- the admin sees everything, users what they owned when it changed and what was transferred to them
+ WHERE ($2
+        OR audit_log.owner_id = requester.id
+        OR (audit_log.event = 'ownership' AND audit_log.user_id = requester.id))
+   AND ($3::int4 IS NULL OR audit_log.memo_group_id = $3)
+   AND ($4::int4 IS NULL OR audit_log.user_group_id = $4)
+   AND ($5::varchar IS NULL OR actor.username = $5 OR affected.username = $5)
+   AND ($6::int8 IS NULL OR audit_log.changed_on >= $6)
+   AND ($7::int8 IS NULL OR audit_log.changed_on <= $7)
+ ORDER BY audit_log.changed_on DESC, audit_log.id DESC;
@@ -1 +0,0 @@
-SELECT * FROM file_user_access($1, $2, $3);
\ No newline at end of file
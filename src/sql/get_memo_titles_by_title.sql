@@ -0,0 +1,9 @@
+SELECT memo.id, memo.title, memo.user_id, memo.savetime
+  FROM memo_visible($1) AS visible
+  JOIN memo ON memo.id = visible.o_memo_id
+ WHERE ($2 = 'all' OR ($2 = 'shared') = visible.o_shared)
+   AND ($3::int4 IS NULL OR memo.group_id = $3)
+   -- cursor, continue after the last memo of the previous page
+   AND ($5::int4 IS NULL OR (COALESCE(memo.title, ''), memo.id) > ($4, $5))
+ ORDER BY COALESCE(memo.title, ''), memo.id
+ LIMIT $6;
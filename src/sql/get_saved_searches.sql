@@ -0,0 +1,7 @@
+SELECT saved_search.id, saved_search.name, saved_search.search, saved_search.group_id, saved_search.owner,
+       saved_search.savetime_from, saved_search.savetime_to, saved_search.scope,
+       saved_search.include_history, saved_search.include_attachments
+FROM saved_search
+JOIN users ON saved_search.user_id = users.id
+WHERE users.username = $1
+ORDER BY saved_search.name;
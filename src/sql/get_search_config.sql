@@ -0,0 +1,4 @@
+SELECT users.search_config::text AS search_config,
+       memo_search_config(users.id)::text AS effective
+FROM users
+WHERE users.username = $1;
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT memo.id, memo.title, memo.user_id, memo.savetime, memo.deleted_on
+FROM memo
+JOIN users ON memo.user_id = users.id
+WHERE users.username = $1
+  AND memo.deleted_on IS NOT NULL
+ORDER BY memo.deleted_on DESC;
@@ -0,0 +1,5 @@
+SELECT
+id,
+username
+FROM users
+WHERE username = $1
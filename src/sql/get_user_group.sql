@@ -0,0 +1,5 @@
+SELECT user_group.id, user_group.user_group_name AS name, user_group.user_id
+FROM user_group
+JOIN users ON user_group.user_id = users.id
+WHERE user_group.id = $1
+  AND users.username = $2;
@@ -1,6 +1,8 @@
 INSERT INTO
-  filestore(id, user_id, filename, memo_group_id, uploaded_on)
-SELECT $1, users.id, $3, memo_group.id, $5
-FROM users LEFT JOIN memo_group ON users.id = memo_group.user_id AND memo_group.id = $4
+  filestore(id, user_id, filename, memo_group_id, uploaded_on, content_text)
+SELECT $1, users.id, $3, $4, $5, $6
+FROM users
 WHERE users.username = $2
-;
\ No newline at end of file
+  -- same rule as Action::Upload: write access on the group is enough, it doesn't have to be owned
+  AND ($4::int4 IS NULL OR memo_group_access($4, $2) >= access_write())
+;
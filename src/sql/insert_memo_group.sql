@@ -0,0 +1,7 @@
+INSERT INTO memo_group (name, user_id, parent_id)
+SELECT $2, users.id, $3
+FROM users
+WHERE users.username = $1
+  -- subgroups only under groups of the same owner
+  AND ($3::int4 IS NULL OR EXISTS (SELECT 1 FROM memo_group AS parent WHERE parent.id = $3 AND parent.user_id = users.id))
+RETURNING id, name;
@@ -0,0 +1,5 @@
+INSERT INTO saved_search (user_id, name, search, group_id, owner, savetime_from, savetime_to, scope, include_history, include_attachments)
+SELECT users.id, $2, $3, $4, $5, $6, $7, $8, $9, $10
+FROM users
+WHERE users.username = $1
+RETURNING id, name, search, group_id, owner, savetime_from, savetime_to, scope, include_history, include_attachments;
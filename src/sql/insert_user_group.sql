@@ -0,0 +1,5 @@
+INSERT INTO user_group (user_group_name, user_id)
+SELECT $2, users.id
+FROM users
+WHERE users.username = $1
+RETURNING id, user_group_name AS name, user_id;
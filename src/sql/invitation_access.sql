@@ -0,0 +1,12 @@
+-- memo groups the user groups of the pending invitations give access to,
+-- with the subgroups inheriting the access as memo_acl_inherited resolves it
+SELECT user_group_invitation.id AS invitation_id, memo_group.id AS memo_group_id,
+       memo_group.name AS memo_group_name, shared.o_access AS access
+FROM user_group_invitation
+JOIN users ON user_group_invitation.user_id = users.id
+CROSS JOIN LATERAL memo_acl_inherited(ARRAY[user_group_invitation.user_group_id]) AS shared
+JOIN memo_group ON shared.o_memo_group_id = memo_group.id
+WHERE users.username = $1
+  AND user_group_invitation.status = 'pending'
+  AND shared.o_access > 0
+ORDER BY memo_group.name;
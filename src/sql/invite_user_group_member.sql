@@ -0,0 +1 @@
+SELECT user_group_invite($1, $2, $3, $4);
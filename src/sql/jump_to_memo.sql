@@ -0,0 +1,19 @@
+-- each branch can use memo_title_trgm_idx, an OR of both would not
+WITH matched AS (
+  -- similar to a part of the title
+  SELECT memo.id
+    FROM memo
+   WHERE unaccent($2) <% unaccent(memo.title)
+  UNION
+  -- or contained in it, with the LIKE wildcards of the query taken literally
+  SELECT memo.id
+    FROM memo
+   WHERE unaccent(memo.title) ILIKE '%' || replace(replace(replace(unaccent($2), '\', '\\'), '%', '\%'), '_', '\_') || '%'
+)
+SELECT memo.id, memo.title, memo.user_id, memo.savetime,
+       word_similarity(unaccent($2), unaccent(memo.title)) AS score
+  FROM matched
+  JOIN memo ON memo.id = matched.id
+  JOIN memo_visible($1) AS visible ON visible.o_memo_id = memo.id
+ ORDER BY score DESC, memo.savetime DESC
+ LIMIT $3;
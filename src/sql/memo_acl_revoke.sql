@@ -0,0 +1 @@
+SELECT memo_acl_revoke($1, $2, $3);
@@ -0,0 +1 @@
+SELECT memo_acl_set($1, $2, $3, $4);
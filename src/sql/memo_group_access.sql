@@ -0,0 +1 @@
+SELECT memo_group_access($1, $2) AS access;
@@ -1,5 +1,6 @@
-SELECT memo_group.id, memo_group.name, memo_group.user_id
+SELECT memo_group.id, memo_group.name, memo_group.parent_id
 FROM memo_group
 JOIN users ON user_id = users.id
-WHERE users.username = $1;
+WHERE users.username = $1
+ORDER BY memo_group.name, memo_group.id;
 
@@ -0,0 +1 @@
+SELECT * FROM memo_history_list($1, $2);
\ No newline at end of file
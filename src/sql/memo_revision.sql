@@ -0,0 +1 @@
+SELECT * FROM memo_history_read($1, $2, $3);
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT revision.*
+  FROM memo_history_list($1, $3) AS list,
+       memo_history_read($1, list.o_id, $3) AS revision
+ WHERE list.o_savetime = $2
+ LIMIT 1;
\ No newline at end of file
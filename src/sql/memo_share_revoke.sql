@@ -0,0 +1 @@
+SELECT memo_share_revoke($1, $2, $3);
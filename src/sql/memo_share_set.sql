@@ -0,0 +1 @@
+SELECT memo_share_set($1, $2, $3, $4);
@@ -0,0 +1,8 @@
+SELECT memo_share.memo_id, memo_share.user_id, users.username, memo_share.access
+FROM memo_share
+JOIN users ON memo_share.user_id = users.id
+JOIN memo ON memo_share.memo_id = memo.id
+JOIN users AS owner ON memo.user_id = owner.id
+WHERE memo_share.memo_id = $1
+  AND owner.username = $2
+ORDER BY users.username;
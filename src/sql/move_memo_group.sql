@@ -0,0 +1 @@
+SELECT memo_group_move($1, $2, $3);
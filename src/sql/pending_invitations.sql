@@ -0,0 +1,10 @@
+SELECT user_group_invitation.id, user_group_invitation.user_group_id, user_group.user_group_name,
+       user_group_invitation.user_id, users.username, inviter.username AS invited_by,
+       user_group_invitation.invited_on, user_group_invitation.status
+FROM user_group_invitation
+JOIN user_group ON user_group_invitation.user_group_id = user_group.id
+JOIN users ON user_group_invitation.user_id = users.id
+JOIN users AS inviter ON user_group_invitation.invited_by = inviter.id
+WHERE users.username = $1
+  AND user_group_invitation.status = 'pending'
+ORDER BY user_group_invitation.invited_on;
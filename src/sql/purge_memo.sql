@@ -0,0 +1,13 @@
+WITH purged AS (
+  DELETE FROM memo
+  USING users
+  WHERE memo.id = $1
+    AND memo.user_id = users.id
+    AND users.username = $2
+    AND memo.deleted_on IS NOT NULL
+  RETURNING memo.id
+), purged_history AS (
+  DELETE FROM memo_history
+  WHERE memo_id IN (SELECT id FROM purged)
+)
+SELECT id FROM purged;
@@ -0,0 +1,9 @@
+WITH purged AS (
+  DELETE FROM memo
+  WHERE memo.deleted_on < $1
+  RETURNING memo.id
+), purged_history AS (
+  DELETE FROM memo_history
+  WHERE memo_id IN (SELECT id FROM purged)
+)
+SELECT id FROM purged;
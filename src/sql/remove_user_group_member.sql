@@ -0,0 +1 @@
+SELECT user_group_member_remove($1, $2, $3);
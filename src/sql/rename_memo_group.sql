@@ -0,0 +1,6 @@
+UPDATE memo_group SET name = $3
+FROM users
+WHERE memo_group.id = $1
+  AND memo_group.user_id = users.id
+  AND users.username = $2
+RETURNING memo_group.id, memo_group.name;
@@ -0,0 +1 @@
+SELECT * FROM memo_restore($1, $2, $3, $4);
\ No newline at end of file
@@ -1,37 +1,70 @@
-select id, title, user_id, savetime 
-  from memo 
- where to_tsvector(unaccent(title || memotext)) @@ to_tsquery(unaccent($2))
-   -- either own memos or shared by others
-   and (
-     user_id in (
-       select id 
-         from users 
-        where users.username = $1
-     )
-    or 
-    id in (
-      select memo.id
-        from memo
-       where memo.group_id in (
-        select memo_acl.memo_group_id
-          from user_group,
-               user_group_detail,
-               memo_acl,
-               users
-         where user_group.id = user_group_detail.user_group_id
-           and user_group.id = memo_acl.user_group_id
-           and user_group_detail.user_id = users.id
-           and user_group.user_id <> users.id
-           and users.username = $1
-       )      
-    ) --end look for memos shared with this user
+with requester as (
+  select users.id from users where users.username = $1
+), search as (
+  -- the query in every configuration the memos are indexed with
+  select configs.search_config, to_tsquery(configs.search_config, unaccent($2)) as tsquery
+    from (select distinct memo_search_config(users.id) as search_config from users) as configs
+)
+select results.*
+  from (
+    -- current text of own memos or shared by others
+    select 'memo'::varchar as source, memo.id, null::int4 as revision_id, null::uuid as file_id,
+           memo.title, memo.user_id, memo.savetime,
+           ts_rank(memo.tsv, search.tsquery) as rank,
+           ts_headline(search.search_config, unaccent(memo.title || memo.memotext), search.tsquery,
+             'MaxFragments=2, MaxWords=20, MinWords=5, FragmentDelimiter=" … "') as snippet
+      from memo_visible($1) as visible
+      join memo on memo.id = visible.o_memo_id
+      join search on search.search_config = memo.search_config
+      join users as owner on memo.user_id = owner.id
+     where memo.tsv @@ search.tsquery
+       and ($3 = 'all' or ($3 = 'shared') = visible.o_shared)
+       and ($4::int4 is null or memo.group_id = $4)
+       and ($5::varchar is null or owner.username = $5)
+       and ($6::int8 is null or memo.savetime >= $6)
+       and ($7::int8 is null or memo.savetime <= $7)
 
-   ) -- end and condition
+    union all
 
+    -- older revisions of the same memos
+    select 'history'::varchar, memo.id, memo_history.id, null::uuid,
+           memo_history.title, memo.user_id, memo_history.savetime,
+           ts_rank(to_tsvector(search.search_config, unaccent(memo_history.title || memo_history.memotext)), search.tsquery),
+           ts_headline(search.search_config, unaccent(memo_history.title || memo_history.memotext), search.tsquery,
+             'MaxFragments=2, MaxWords=20, MinWords=5, FragmentDelimiter=" … "')
+      from memo_visible($1) as visible
+      join memo on memo.id = visible.o_memo_id
+      join search on search.search_config = memo.search_config
+      join memo_history on memo_history.memo_id = memo.id
+      join users as owner on memo.user_id = owner.id
+     where $8
+       and to_tsvector(search.search_config, unaccent(memo_history.title || memo_history.memotext)) @@ search.tsquery
+       and ($3 = 'all' or ($3 = 'shared') = visible.o_shared)
+       and ($4::int4 is null or memo.group_id = $4)
+       and ($5::varchar is null or owner.username = $5)
+       and ($6::int8 is null or memo_history.savetime >= $6)
+       and ($7::int8 is null or memo_history.savetime <= $7)
 
-union all
+    union all
 
-select 0, $1, users.id, 0
-  from users
- where users.username = $1
-;
\ No newline at end of file
+    -- text of uploaded files, own or in a memo group shared with the user
+    select 'attachment'::varchar, null::int4, null::int4, filestore.id,
+           filestore.filename, filestore.user_id, filestore.uploaded_on,
+           ts_rank(to_tsvector(search.search_config, unaccent(filestore.content_text)), search.tsquery),
+           ts_headline(search.search_config, unaccent(filestore.content_text), search.tsquery,
+             'MaxFragments=2, MaxWords=20, MinWords=5, FragmentDelimiter=" … "')
+      from filestore
+      join users as owner on filestore.user_id = owner.id
+      join search on search.search_config = memo_search_config(filestore.user_id),
+           requester
+     where $9
+       and (filestore.user_id = requester.id or filestore.memo_group_id in (select memo_group_shared($1)))
+       and to_tsvector(search.search_config, unaccent(filestore.content_text)) @@ search.tsquery
+       and ($3 = 'all' or ($3 = 'shared') = (filestore.user_id <> requester.id))
+       and ($4::int4 is null or filestore.memo_group_id = $4)
+       and ($5::varchar is null or owner.username = $5)
+       and ($6::int8 is null or filestore.uploaded_on >= $6)
+       and ($7::int8 is null or filestore.uploaded_on <= $7)
+  ) as results
+ order by results.rank desc, results.savetime desc
+;
@@ -0,0 +1 @@
+SELECT memo_transfer($1, $2, $3, $4);
@@ -0,0 +1 @@
+SELECT memo_group_transfer($1, $2, $3, $4);
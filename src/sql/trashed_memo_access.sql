@@ -0,0 +1 @@
+SELECT memo_access($1, $2, true) AS access;
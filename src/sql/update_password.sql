@@ -1,3 +1 @@
-UPDATE users
-SET salt=$1, pbkdf2 = $2
-WHERE username = $3;
\ No newline at end of file
+SELECT user_password_set($4, $3, $1, $2);
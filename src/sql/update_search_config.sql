@@ -0,0 +1 @@
+SELECT user_search_config_set($1, $2);
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT users.id, users.username
+FROM user_group_detail
+JOIN users ON user_group_detail.user_id = users.id
+WHERE user_group_detail.user_group_id = $1
+ORDER BY users.username;